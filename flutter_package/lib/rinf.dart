@@ -3,31 +3,59 @@ library;
 
 import 'dart:typed_data';
 import 'dart:convert';
-import 'package:flutter/foundation.dart' show debugPrint;
+import 'package:flutter/foundation.dart' show debugPrint, kDebugMode;
 import 'src/structure.dart';
 import 'src/interface.dart';
+import 'src/lifecycle.dart';
+import 'src/logging.dart';
+import 'src/panic.dart';
 
 export 'src/structure.dart' show RustSignalPack;
+export 'src/logging.dart' show RustLogLevel, RustLogRecord, rustLogStream;
+export 'src/panic.dart' show RustPanicReport, rustPanicStream;
 
 /// Starts the `main` function in Rust.
+/// When multiple Rust libraries are used in one app,
+/// each library should be initialized with the `namespace`
+/// given to `write_interface!` in Rust, along with its own
+/// `assignRustSignal` generated by `rinf gen`.
 Future<void> initializeRust(
   AssignRustSignal assignRustSignal, {
   String? compiledLibPath,
+  String namespace = '',
 }) async {
   // Override the default library path if provided.
   if (compiledLibPath != null) {
-    setCompiledLibPathReal(compiledLibPath);
+    setCompiledLibPathReal(namespace, compiledLibPath);
   }
 
   // Add the print delegation endpoint.
-  assignRustSignal['RinfOut'] = (messageBytes, binary) {
+  assignRustSignal[rinfOutEndpointId] = (messageBytes, binary) {
     final rustReport = utf8.decode(binary);
     debugPrint(rustReport, wrapWidth: 1024);
   };
 
+  // Add the structured logging endpoint.
+  // Records are also printed in debug mode for convenience.
+  assignRustSignal[rinfLogEndpointId] = (messageBytes, binary) {
+    final record = receiveRustLog(messageBytes);
+    if (kDebugMode) {
+      debugPrint(record.toString(), wrapWidth: 1024);
+    }
+  };
+
+  // Add the panic reporting endpoint.
+  // Panics are already printed by Rust in debug mode.
+  assignRustSignal[rinfPanicEndpointId] = (messageBytes, binary) {
+    receiveRustPanic(messageBytes);
+  };
+
   // Prepare the interface with Rust.
-  await prepareInterfaceReal(assignRustSignal);
-  startRustLogicReal();
+  await prepareInterfaceReal(namespace, assignRustSignal);
+  startRustLogicReal(namespace);
+
+  // Forward app lifecycle events to Rust.
+  startLifecycleObserver(namespace);
 }
 
 /// Terminates all Rust tasks by dropping the async runtime.
@@ -37,16 +65,18 @@ Future<void> initializeRust(
 /// to prevent potential resource leaks from Rust.
 /// On the web, this function has no effect as tasks are managed by
 /// the JavaScript runtime rather than the Rust async runtime.
-void finalizeRust() {
-  stopRustLogicReal();
+void finalizeRust({String namespace = ''}) {
+  stopLifecycleObserver(namespace);
+  stopRustLogicReal(namespace);
 }
 
-/// Sends a signal to Rust by using a symbol
-/// that exists inside local space of the loaded dynamic library.
+/// Sends a signal to Rust with the endpoint ID assigned by `rinf gen`.
+/// All signals go through a single function exported by Rust.
 void sendDartSignal(
-  String endpointSymbol,
+  int endpointId,
   Uint8List messageBytes,
-  Uint8List binary,
-) {
-  sendDartSignalReal(endpointSymbol, messageBytes, binary);
+  Uint8List binary, {
+  String namespace = '',
+}) {
+  sendDartSignalReal(namespace, endpointId, messageBytes, binary);
 }
@@ -0,0 +1,43 @@
+import 'dart:convert';
+import 'dart:typed_data';
+
+/// Reads values in the bincode layout
+/// that Rust uses for its internal endpoints.
+class BincodeReader {
+  final Uint8List bytes;
+  final ByteData data;
+  int offset = 0;
+
+  BincodeReader(this.bytes) : data = ByteData.sublistView(bytes);
+
+  int readUint8() {
+    final value = data.getUint8(offset);
+    offset += 1;
+    return value;
+  }
+
+  int readUint32() {
+    final value = data.getUint32(offset, Endian.little);
+    offset += 4;
+    return value;
+  }
+
+  /// Reads a 64-bit length with two 32-bit reads,
+  /// because `getUint64` is not supported on the web.
+  int readLength() {
+    final low = readUint32();
+    final high = readUint32();
+    return high * 0x100000000 + low;
+  }
+
+  String readString() {
+    final length = readLength();
+    final value = utf8.decode(bytes.sublist(offset, offset + length));
+    offset += length;
+    return value;
+  }
+
+  T? readOption<T>(T Function() readValue) {
+    return readUint8() == 0 ? null : readValue();
+  }
+}
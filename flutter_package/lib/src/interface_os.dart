@@ -6,51 +6,78 @@ import 'load_os.dart';
 import 'structure.dart';
 
 /// Sets the exact file path of the dynamic library
-/// compiled from the `hub` crate.
-void setCompiledLibPathReal(String path) {
-  overrideLibPath(path);
+/// compiled from the `hub` crate or a namespaced crate.
+void setCompiledLibPathReal(String namespace, String path) {
+  overrideLibPath(namespace, path);
 }
 
-Future<void> prepareInterfaceReal(AssignRustSignal assignRustSignal) async {
+Future<void> prepareInterfaceReal(
+  String namespace,
+  AssignRustSignal assignRustSignal,
+) async {
   // Prepare ports for communication over isolates.
+  // Each namespace has its own port.
   final rustSignalPort = ReceivePort();
 
   // Listen to Rust via isolate port.
   rustSignalPort.listen((rustSignalRaw) {
-    String endpoint = rustSignalRaw[0];
-    Uint8List? messageBytes = rustSignalRaw[1];
-    Uint8List? binary = rustSignalRaw[2];
-
-    // Rust will send null if the vector is empty.
-    // Converting is needed on the Dart side.
-    binary ??= Uint8List(0);
-    // Rust will send null if the vector is empty.
-    // Converting is needed on the Dart side.
-    messageBytes ??= Uint8List(0);
-
-    assignRustSignal[endpoint]!(messageBytes, binary);
+    int endpointId = rustSignalRaw[0];
+    if (endpointId == rinfBatchEndpointId) {
+      // Unpack the signals in the order they were sent.
+      List<dynamic> packedSignals = rustSignalRaw[1];
+      for (final packedSignal in packedSignals) {
+        assignRustSignalRaw(assignRustSignal, packedSignal);
+      }
+    } else {
+      assignRustSignalRaw(assignRustSignal, rustSignalRaw);
+    }
   });
 
   // Make Rust prepare its isolate to send data to Dart.
   // This is handled by `allo_isolate`.
-  rustLibrary.prepareIsolate(
+  getRustLibrary(namespace).prepareIsolate(
     NativeApi.postCObject,
     rustSignalPort.sendPort.nativePort,
   );
 }
 
-void startRustLogicReal() {
-  rustLibrary.startRustLogic();
+void startRustLogicReal(String namespace) {
+  getRustLibrary(namespace).startRustLogic();
 }
 
-void stopRustLogicReal() {
-  rustLibrary.stopRustLogic();
+void stopRustLogicReal(String namespace) {
+  getRustLibrary(namespace).stopRustLogic();
 }
 
 void sendDartSignalReal(
-  String endpointSymbol,
+  String namespace,
+  int endpointId,
   Uint8List messageBytes,
   Uint8List binary,
 ) {
-  rustLibrary.sendDartSignal(endpointSymbol, messageBytes, binary);
+  getRustLibrary(namespace).sendDartSignal(
+    endpointId,
+    messageBytes,
+    binary,
+  );
+}
+
+/// Passes a single Rust signal from the isolate port
+/// to its handler.
+void assignRustSignalRaw(
+  AssignRustSignal assignRustSignal,
+  List<dynamic> rustSignalRaw,
+) {
+  int endpointId = rustSignalRaw[0];
+  Uint8List? messageBytes = rustSignalRaw[1];
+  Uint8List? binary = rustSignalRaw[2];
+
+  // Rust will send null if the vector is empty.
+  // Converting is needed on the Dart side.
+  binary ??= Uint8List(0);
+  // Rust will send null if the vector is empty.
+  // Converting is needed on the Dart side.
+  messageBytes ??= Uint8List(0);
+
+  assignRustSignal[endpointId]!(messageBytes, binary);
 }
@@ -9,41 +9,84 @@ import 'structure.dart';
 
 /// Sets the path to the JavaScript module
 /// that needs to be loaded.
-void setCompiledLibPathReal(String path) {
-  setJsLibPath(path);
+void setCompiledLibPathReal(String namespace, String path) {
+  setJsLibPath(namespace, path);
 }
 
-Future<void> prepareInterfaceReal(AssignRustSignal assignRustSignal) async {
+Future<void> prepareInterfaceReal(
+  String namespace,
+  AssignRustSignal assignRustSignal,
+) async {
   // Load the JavaScript module.
-  await loadJsFile();
+  await loadJsFile(namespace);
 
   // Listen to Rust via JavaScript.
-  rinfBindingsObject['rinf_send_rust_signal_extern'] =
-      ((String endpoint, JSObject messageBytesJS, JSObject binaryJS) {
-    final messageBytes = (messageBytesJS as JSUint8Array).toDart;
-    final binary = (binaryJS as JSUint8Array).toDart;
-    assignRustSignal[endpoint]!(messageBytes, binary);
+  // Each namespace has its own bindings object.
+  getRinfBindingsObject(namespace)['rinf_send_rust_signal_extern'] =
+      ((JSNumber endpointIdJS, JSObject messageBytesJS, JSObject binaryJS) {
+    final endpointId = endpointIdJS.toDartInt;
+    if (endpointId == rinfBatchEndpointId) {
+      // Unpack the signals in the order they were sent.
+      final packedSignals = (messageBytesJS as JSArray<JSArray>).toDart;
+      for (final packedSignal in packedSignals) {
+        final items = packedSignal.toDart;
+        assignRustSignalJs(
+          assignRustSignal,
+          items[0] as JSNumber,
+          items[1] as JSObject,
+          items[2] as JSObject,
+        );
+      }
+    } else {
+      assignRustSignalJs(
+        assignRustSignal,
+        endpointIdJS,
+        messageBytesJS,
+        binaryJS,
+      );
+    }
   }).toJS;
 }
 
-void startRustLogicReal() {
-  if (wasAlreadyLoaded) {
+/// Passes a single Rust signal from JavaScript
+/// to its handler.
+/// The message bytes are a view over the WebAssembly memory
+/// that is only valid during this call, so handlers decode them right away.
+/// The binary is a copy that Dart owns.
+void assignRustSignalJs(
+  AssignRustSignal assignRustSignal,
+  JSNumber endpointIdJS,
+  JSObject messageBytesJS,
+  JSObject binaryJS,
+) {
+  final endpointId = endpointIdJS.toDartInt;
+  final messageBytes = (messageBytesJS as JSUint8Array).toDart;
+  final binary = (binaryJS as JSUint8Array).toDart;
+  assignRustSignal[endpointId]!(messageBytes, binary);
+}
+
+void startRustLogicReal(String namespace) {
+  if (alreadyLoadedNamespaces.contains(namespace)) {
     return;
   }
-  wasmBindingsObject.callMethod('rinf_start_rust_logic_extern'.toJS);
+  getWasmBindingsObject(namespace).callMethod(
+    'rinf_start_rust_logic_extern'.toJS,
+  );
 }
 
-void stopRustLogicReal() {
+void stopRustLogicReal(String namespace) {
   // Dummy function to match the structure of native platforms.
 }
 
 void sendDartSignalReal(
-  String endpointSymbol,
+  String namespace,
+  int endpointId,
   Uint8List messageBytes,
   Uint8List binary,
 ) {
-  wasmBindingsObject.callMethod(
-    endpointSymbol.toJS,
+  getWasmBindingsObject(namespace).callMethod(
+    'rinf_send_dart_signal'.toJS,
+    endpointId.toJS,
     messageBytes.toJS,
     binary.toJS,
   );
@@ -0,0 +1,90 @@
+import 'dart:typed_data';
+import 'package:flutter/widgets.dart';
+import 'interface.dart';
+import 'structure.dart';
+
+/// Code of the memory pressure event, which comes after the codes
+/// of `AppLifecycleState`.
+const _memoryPressureCode = 5;
+
+/// Returns the code of an app state, which Rust understands.
+int _getStateCode(AppLifecycleState state) {
+  switch (state) {
+    case AppLifecycleState.resumed:
+      return 0;
+    case AppLifecycleState.inactive:
+      return 1;
+    case AppLifecycleState.hidden:
+      return 2;
+    case AppLifecycleState.paused:
+      return 3;
+    case AppLifecycleState.detached:
+      return 4;
+  }
+}
+
+/// Forwards app lifecycle events to a Rust library,
+/// so that `lifecycle()` in Rust can receive them.
+class RustLifecycleObserver with WidgetsBindingObserver {
+  /// The namespace of the Rust library.
+  final String namespace;
+
+  /// Constructs a lifecycle observer.
+  /// This constructor is not intended for external use outside the library.
+  RustLifecycleObserver(this.namespace);
+
+  @override
+  void didChangeAppLifecycleState(AppLifecycleState state) {
+    sendLifecycleCode(_getStateCode(state));
+  }
+
+  @override
+  void didHaveMemoryPressure() {
+    sendLifecycleCode(_memoryPressureCode);
+  }
+
+  /// Sends the current app state, if Flutter knows it.
+  void sendCurrentState() {
+    final state = WidgetsBinding.instance.lifecycleState;
+    if (state != null) {
+      sendLifecycleCode(_getStateCode(state));
+    }
+  }
+
+  /// Sends an event code as a bincode-encoded `u32`.
+  void sendLifecycleCode(int code) {
+    final messageBytes = Uint8List(4);
+    messageBytes.buffer.asByteData().setUint32(0, code, Endian.little);
+    sendDartSignalReal(
+      namespace,
+      rinfLifecycleEndpointId,
+      messageBytes,
+      Uint8List(0),
+    );
+  }
+}
+
+final _lifecycleObservers = <String, RustLifecycleObserver>{};
+
+/// Starts forwarding app lifecycle events to the Rust library
+/// with the given namespace.
+void startLifecycleObserver(String namespace) {
+  final binding = WidgetsFlutterBinding.ensureInitialized();
+  final previous = _lifecycleObservers.remove(namespace);
+  if (previous != null) {
+    binding.removeObserver(previous);
+  }
+  final observer = RustLifecycleObserver(namespace);
+  _lifecycleObservers[namespace] = observer;
+  binding.addObserver(observer);
+  observer.sendCurrentState();
+}
+
+/// Stops forwarding app lifecycle events to the Rust library
+/// with the given namespace.
+void stopLifecycleObserver(String namespace) {
+  final observer = _lifecycleObservers.remove(namespace);
+  if (observer != null) {
+    WidgetsBinding.instance.removeObserver(observer);
+  }
+}
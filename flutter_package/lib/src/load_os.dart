@@ -3,17 +3,22 @@ import 'dart:ffi';
 import 'dart:typed_data';
 import 'package:ffi/ffi.dart';
 import 'package:rinf/src/platform_utils.dart';
+import 'structure.dart';
 
-String? libPathOverride;
+/// Library paths provided by the user for each namespace.
+final Map<String, String> libPathOverrides = {};
 
-void overrideLibPath(String path) {
-  libPathOverride = path;
+void overrideLibPath(String namespace, String path) {
+  libPathOverrides[namespace] = path;
 }
 
-RustLibrary loadRustLibrary() {
+RustLibrary loadRustLibrary(String namespace) {
   // Use provided dynamic library path if possible.
   // Otherewise, use the default path.
-  final override = libPathOverride;
+  // A namespaced library is expected to be named after its namespace.
+  final override = libPathOverrides[namespace];
+  final name = namespace.isEmpty ? 'hub' : namespace;
+  final framework = namespace.isEmpty ? 'rinf' : namespace;
   String libPath;
   if (override != null) {
     // Use the override path if provided.
@@ -21,17 +26,17 @@ RustLibrary loadRustLibrary() {
   } else {
     // Load library files from the app bundle.
     if (Platform.isLinux) {
-      libPath = 'libhub.so';
+      libPath = 'lib$name.so';
     } else if (Platform.isAndroid) {
-      libPath = 'libhub.so';
+      libPath = 'lib$name.so';
     } else if (PlatformUtils.isOhos) {
-      libPath = 'libhub.so';
+      libPath = 'lib$name.so';
     } else if (Platform.isWindows) {
-      libPath = 'hub.dll';
+      libPath = '$name.dll';
     } else if (Platform.isIOS) {
-      libPath = 'rinf.framework/rinf';
+      libPath = '$framework.framework/$framework';
     } else if (Platform.isMacOS) {
-      libPath = 'rinf.framework/rinf';
+      libPath = '$framework.framework/$framework';
     } else {
       throw UnsupportedError('This operating system is not supported');
     }
@@ -41,8 +46,10 @@ RustLibrary loadRustLibrary() {
   final lib = DynamicLibrary.open(libPath);
 
   // Create the FFI wrapper instance.
-  if (useLocalSpaceSymbols()) {
-    return RustLibraryLocal(lib);
+  // Symbols of `RustLibraryGlobal` are fixed at compile time,
+  // so namespaced symbols are always searched in the library.
+  if (namespace.isNotEmpty || useLocalSpaceSymbols()) {
+    return RustLibraryLocal(lib, getSymbolPrefix(namespace));
   } else {
     return RustLibraryGlobal(lib);
   }
@@ -75,8 +82,18 @@ bool useLocalSpaceSymbols() {
   return false;
 }
 
-/// The central interface for calling native function.
-final rustLibrary = loadRustLibrary();
+/// Loaded Rust libraries for each namespace.
+final Map<String, RustLibrary> _rustLibraries = {};
+
+/// Returns the central interface for calling native functions
+/// of the library with the given namespace,
+/// loading the library on first use.
+RustLibrary getRustLibrary(String namespace) {
+  return _rustLibraries.putIfAbsent(
+    namespace,
+    () => loadRustLibrary(namespace),
+  );
+}
 
 // Common type aliases.
 // These exist for better readability of the code.
@@ -86,9 +103,9 @@ typedef PostCObjectPtr = Pointer<NativeFunction<PostCObjectInner>>;
 typedef PrepareIsolateExtern = Void Function(PostCObjectPtr, Int64);
 typedef PrepareIsolateWrapped = void Function(PostCObjectPtr, int);
 typedef SendDartSignalExtern = Void Function(
-    Pointer<Uint8>, UintPtr, Pointer<Uint8>, UintPtr);
+    Uint32, Pointer<Uint8>, UintPtr, Pointer<Uint8>, UintPtr);
 typedef SendDartSignalWrapped = void Function(
-    Pointer<Uint8>, int, Pointer<Uint8>, int);
+    int, Pointer<Uint8>, int, Pointer<Uint8>, int);
 
 /// Abstract class for unifying the interface
 /// for calling native functions.
@@ -97,7 +114,7 @@ abstract class RustLibrary {
   void stopRustLogic();
   void prepareIsolate(PostCObjectPtr storePostObject, int port);
   void sendDartSignal(
-    String endpointSymbol,
+    int endpointId,
     Uint8List messageBytes,
     Uint8List binary,
   );
@@ -116,7 +133,6 @@ class RustLibraryGlobal extends RustLibrary {
   // loaded into global space with `RTLD_GLOBAL` configuration.
 
   final DynamicLibrary lib;
-  final Map<String, SendDartSignalWrapped> sendDartSignalExterns = {};
 
   RustLibraryGlobal(this.lib);
 
@@ -135,6 +151,18 @@ class RustLibraryGlobal extends RustLibrary {
     int port,
   );
 
+  @Native<SendDartSignalExtern>(
+    isLeaf: true,
+    symbol: 'rinf_send_dart_signal',
+  )
+  external static void sendDartSignalExtern(
+    int endpointId,
+    Pointer<Uint8> messagePointer,
+    int messageSize,
+    Pointer<Uint8> binaryPointer,
+    int binarySize,
+  );
+
   @override
   void startRustLogic() {
     startRustLogicExtern();
@@ -152,42 +180,20 @@ class RustLibraryGlobal extends RustLibrary {
 
   @override
   void sendDartSignal(
-    String endpointSymbol,
+    int endpointId,
     Uint8List messageBytes,
     Uint8List binary,
   ) {
-    // Using the `@Native` annotation and avoiding `malloc`
-    // with generated messages is not possible
-    // because we cannot pass symbols dynamically.
-    // Therefore, we search for the symbols in the dynamic library.
-    // Also, Dart's native assets feature is not fully reliable yet.
-
-    final Pointer<Uint8> messageMemory = malloc.allocate(messageBytes.length);
-    messageMemory.asTypedList(messageBytes.length).setAll(0, messageBytes);
-
-    final Pointer<Uint8> binaryMemory = malloc.allocate(binary.length);
-    binaryMemory.asTypedList(binary.length).setAll(0, binary);
-
-    // Cache the dynamic library functions
-    // to reduce symbol lookup overhead.
-    var sendDartSignalExtern = sendDartSignalExterns[endpointSymbol];
-    if (sendDartSignalExtern == null) {
-      sendDartSignalExtern =
-          lib.lookupFunction<SendDartSignalExtern, SendDartSignalWrapped>(
-        endpointSymbol,
-      );
-      sendDartSignalExterns[endpointSymbol] = sendDartSignalExtern;
-    }
-
+    // Because all signals share a single function,
+    // the `@Native` annotation can be used
+    // to pass Dart's memory directly without `malloc`.
     sendDartSignalExtern(
-      messageMemory,
+      endpointId,
+      messageBytes.address,
       messageBytes.length,
-      binaryMemory,
+      binary.address,
       binary.length,
     );
-
-    malloc.free(messageMemory);
-    malloc.free(binaryMemory);
   }
 }
 
@@ -200,18 +206,22 @@ class RustLibraryLocal extends RustLibrary {
   late void Function() startRustLogicExtern;
   late void Function() stopRustLogicExtern;
   late PrepareIsolateWrapped prepareIsolateExtern;
-  final Map<String, SendDartSignalWrapped> sendDartSignalExterns = {};
+  late SendDartSignalWrapped sendDartSignalExtern;
 
-  RustLibraryLocal(this.lib) {
+  RustLibraryLocal(this.lib, String symbolPrefix) {
     startRustLogicExtern = lib.lookupFunction<Void Function(), void Function()>(
-      'rinf_start_rust_logic_extern',
+      '${symbolPrefix}start_rust_logic_extern',
     );
     stopRustLogicExtern = lib.lookupFunction<Void Function(), void Function()>(
-      'rinf_stop_rust_logic_extern',
+      '${symbolPrefix}stop_rust_logic_extern',
     );
     prepareIsolateExtern =
         lib.lookupFunction<PrepareIsolateExtern, PrepareIsolateWrapped>(
-      'rinf_prepare_isolate_extern',
+      '${symbolPrefix}prepare_isolate_extern',
+    );
+    sendDartSignalExtern =
+        lib.lookupFunction<SendDartSignalExtern, SendDartSignalWrapped>(
+      '${symbolPrefix}send_dart_signal',
     );
   }
 
@@ -232,7 +242,7 @@ class RustLibraryLocal extends RustLibrary {
 
   @override
   void sendDartSignal(
-    String endpointSymbol,
+    int endpointId,
     Uint8List messageBytes,
     Uint8List binary,
   ) {
@@ -242,18 +252,8 @@ class RustLibraryLocal extends RustLibrary {
     final Pointer<Uint8> binaryMemory = malloc.allocate(binary.length);
     binaryMemory.asTypedList(binary.length).setAll(0, binary);
 
-    // Cache the dynamic library functions
-    // to reduce symbol lookup overhead.
-    var sendDartSignalExtern = sendDartSignalExterns[endpointSymbol];
-    if (sendDartSignalExtern == null) {
-      sendDartSignalExtern =
-          lib.lookupFunction<SendDartSignalExtern, SendDartSignalWrapped>(
-        endpointSymbol,
-      );
-      sendDartSignalExterns[endpointSymbol] = sendDartSignalExtern;
-    }
-
     sendDartSignalExtern(
+      endpointId,
       messageMemory,
       messageBytes.length,
       binaryMemory,
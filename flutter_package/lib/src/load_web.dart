@@ -5,31 +5,59 @@ import 'dart:js_interop';
 import 'dart:js_interop_unsafe';
 import 'package:web/web.dart';
 
-String? jsLibPath;
+/// JavaScript module paths provided by the user for each namespace.
+final Map<String, String> jsLibPaths = {};
 
-void setJsLibPath(String path) {
-  jsLibPath = path;
+void setJsLibPath(String namespace, String path) {
+  jsLibPaths[namespace] = path;
 }
 
-bool wasAlreadyLoaded = false;
-final rinfBindingsObject = globalContext['rinfBindings'] as JSObject;
-final wasmBindingsObject = globalContext['wasmBindings'] as JSObject;
+/// Namespaces whose JavaScript module was already loaded
+/// before the latest Dart hot restart.
+final Set<String> alreadyLoadedNamespaces = {};
+
+/// Returns the suffix of global JavaScript objects for the namespace,
+/// such as `rinfBindings_sdk`.
+/// This matches the names that Rust looks for.
+String getBindingsSuffix(String namespace) {
+  return namespace.isEmpty ? '' : '_$namespace';
+}
+
+/// The JavaScript object used by Rust
+/// to call functions defined in Dart.
+JSObject getRinfBindingsObject(String namespace) {
+  final suffix = getBindingsSuffix(namespace);
+  return globalContext['rinfBindings$suffix'] as JSObject;
+}
+
+/// The JavaScript object with functions exported from Rust.
+JSObject getWasmBindingsObject(String namespace) {
+  final suffix = getBindingsSuffix(namespace);
+  return globalContext['wasmBindings$suffix'] as JSObject;
+}
+
+Future<void> loadJsFile(String namespace) async {
+  final suffix = getBindingsSuffix(namespace);
+  final rinfBindingsName = 'rinfBindings$suffix';
+  final wasmBindingsName = 'wasmBindings$suffix';
 
-Future<void> loadJsFile() async {
   // When Dart performs hot restart,
   // the `rinfBindings` JavaScript object is already defined
   // as a global JavaScript variable.
-  wasAlreadyLoaded = globalContext.hasProperty('rinfBindings'.toJS).toDart;
+  final wasAlreadyLoaded =
+      globalContext.hasProperty(rinfBindingsName.toJS).toDart;
 
   // Stop loading if it already has been done.
   if (wasAlreadyLoaded) {
+    alreadyLoadedNamespaces.add(namespace);
     return;
   }
 
   // Create the namespace JavaScript object.
   // This namespace object is used by Rust
   // to call functions defined in Dart.
-  globalContext['rinfBindings'] = JSObject();
+  globalContext[rinfBindingsName] = JSObject();
+  final rinfBindingsObject = getRinfBindingsObject(namespace);
 
   // Prepare to await the module load.
   final loadCompleter = Completer<void>();
@@ -40,20 +68,26 @@ Future<void> loadJsFile() async {
   // Get the domain and the base path.
   // Flutter app doesn't always have the top-level path of the domain.
   // Sometimes, the flutter app might be placed in a lower path.
+  // A namespaced module is expected to be named after its namespace.
   final baseHref = Uri.base;
-  final path = jsLibPath ?? 'pkg/hub.js';
+  final defaultPath = namespace.isEmpty ? 'pkg/hub.js' : 'pkg/$namespace.js';
+  final path = jsLibPaths[namespace] ?? defaultPath;
   final fullUrl = baseHref.resolve(path);
 
+  // Rust loads the same module in a web worker
+  // when it runs in a dedicated web worker.
+  rinfBindingsObject['jsModuleUrl'] = fullUrl.toString().toJS;
+
   // Insert the script element into the document head.
   // This will load the webassembly module.
   final scriptElement = HTMLScriptElement();
   scriptElement.type = 'module';
   scriptElement.innerHTML = '''
 import init, * as wasmBindings from "$fullUrl";
-globalThis.wasmBindings = wasmBindings;
+globalThis.$wasmBindingsName = wasmBindings;
 await init();
-rinfBindings.completeRinfLoad();
-delete rinfBindings.completeRinfLoad;
+$rinfBindingsName.completeRinfLoad();
+delete $rinfBindingsName.completeRinfLoad;
 '''
       .toJS;
   document.head!.append(scriptElement);
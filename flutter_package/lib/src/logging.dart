@@ -0,0 +1,91 @@
+import 'dart:async';
+import 'dart:typed_data';
+import 'bincode.dart';
+
+/// Severity of a log record from Rust.
+/// Values are ordered from the most severe to the most verbose.
+enum RustLogLevel { error, warn, info, debug, trace }
+
+/// A structured log record sent from Rust
+/// through the `log` or `tracing` bridge of the Rinf crate.
+/// Unlike `debug_print!`, these records are also sent in release mode.
+class RustLogRecord {
+  /// Severity of this record.
+  final RustLogLevel level;
+
+  /// The module or component in Rust that produced this record.
+  final String target;
+
+  /// The formatted log message.
+  final String message;
+
+  /// The Rust source file that produced this record, if known.
+  final String? file;
+
+  /// The line in the Rust source file, if known.
+  final int? line;
+
+  /// Extra key-value pairs attached to this record.
+  final Map<String, String> fields;
+
+  /// Constructs a log record.
+  /// This constructor is not intended for external use outside the library.
+  RustLogRecord(
+    this.level,
+    this.target,
+    this.message,
+    this.file,
+    this.line,
+    this.fields,
+  );
+
+  @override
+  String toString() {
+    final levelName = level.name.toUpperCase();
+    final buffer = StringBuffer('[$levelName $target] $message');
+    if (fields.isNotEmpty) {
+      final pairs = fields.entries.map((e) => '${e.key}=${e.value}');
+      buffer.write(' {${pairs.join(', ')}}');
+    }
+    final file = this.file;
+    if (file != null) {
+      buffer.write(line == null ? ' ($file)' : ' ($file:$line)');
+    }
+    return buffer.toString();
+  }
+}
+
+final _rustLogController = StreamController<RustLogRecord>.broadcast();
+
+/// A broadcast stream of log records from Rust.
+/// Listen to this stream to collect Rust logs,
+/// such as for forwarding them to a crash reporting service.
+/// Records are filtered by level in Rust before being sent.
+Stream<RustLogRecord> get rustLogStream => _rustLogController.stream;
+
+/// Decodes a log record from Rust and adds it to the log stream.
+RustLogRecord receiveRustLog(Uint8List messageBytes) {
+  final record = _readLogRecord(messageBytes);
+  _rustLogController.add(record);
+  return record;
+}
+
+/// Reads the bincode layout written by `send_log_record` in Rust.
+RustLogRecord _readLogRecord(Uint8List messageBytes) {
+  final reader = BincodeReader(messageBytes);
+  // Rust levels start from 1, and unknown levels are treated as `trace`.
+  final levelIndex = reader.readUint8() - 1;
+  final isKnown = levelIndex >= 0 && levelIndex < RustLogLevel.values.length;
+  final level = isKnown ? RustLogLevel.values[levelIndex] : RustLogLevel.trace;
+  final target = reader.readString();
+  final message = reader.readString();
+  final file = reader.readOption(reader.readString);
+  final line = reader.readOption(reader.readUint32);
+  final fields = <String, String>{};
+  final fieldCount = reader.readLength();
+  for (var i = 0; i < fieldCount; i++) {
+    final key = reader.readString();
+    fields[key] = reader.readString();
+  }
+  return RustLogRecord(level, target, message, file, line, fields);
+}
@@ -0,0 +1,59 @@
+import 'dart:async';
+import 'dart:typed_data';
+import 'bincode.dart';
+
+/// Information about a panic that occurred in Rust.
+/// Reports are sent only when `send_to_dart` is enabled
+/// with `set_panic_reporting` in Rust, in any build mode.
+class RustPanicReport {
+  /// The panic message.
+  final String message;
+
+  /// The source location of the panic, such as `src/lib.rs:10:5`.
+  final String? location;
+
+  /// The name of the Rust thread that panicked, if it has one.
+  final String? thread;
+
+  /// The backtrace of the panic, if it was captured.
+  final String? backtrace;
+
+  /// Constructs a panic report.
+  /// This constructor is not intended for external use outside the library.
+  RustPanicReport(this.message, this.location, this.thread, this.backtrace);
+
+  @override
+  String toString() {
+    final buffer = StringBuffer("Thread '${thread ?? '<unnamed>'}' panicked");
+    final location = this.location;
+    if (location != null) {
+      buffer.write(' at $location');
+    }
+    buffer.write(':\n$message');
+    final backtrace = this.backtrace;
+    if (backtrace != null) {
+      buffer.write('\n$backtrace');
+    }
+    return buffer.toString();
+  }
+}
+
+final _rustPanicController = StreamController<RustPanicReport>.broadcast();
+
+/// A broadcast stream of panic reports from Rust.
+/// Listen to this stream to handle Rust panics,
+/// such as for forwarding them to a crash reporting service.
+Stream<RustPanicReport> get rustPanicStream => _rustPanicController.stream;
+
+/// Decodes a panic report from Rust and adds it to the panic stream.
+RustPanicReport receiveRustPanic(Uint8List messageBytes) {
+  final reader = BincodeReader(messageBytes);
+  final report = RustPanicReport(
+    reader.readString(),
+    reader.readOption(reader.readString),
+    reader.readOption(reader.readString),
+    reader.readOption(reader.readString),
+  );
+  _rustPanicController.add(report);
+  return report;
+}
@@ -3,7 +3,30 @@ import 'dart:typed_data';
 /// This type represents a function
 /// that can accept raw signal data from Rust
 /// and handle it accordingly.
-typedef AssignRustSignal = Map<String, Function(Uint8List, Uint8List)>;
+/// Keys are endpoint IDs assigned by `rinf gen`.
+typedef AssignRustSignal = Map<int, Function(Uint8List, Uint8List)>;
+
+/// Endpoint IDs of Rust signals that are handled by Rinf itself.
+/// IDs below 16 are reserved for Rinf,
+/// and `rinf gen` assigns IDs to generated signals starting from 16.
+const rinfOutEndpointId = 0;
+const rinfLogEndpointId = 1;
+const rinfPanicEndpointId = 2;
+
+/// Endpoint ID of a message that packs multiple Rust signals,
+/// which are sent together when batching is used in Rust.
+const rinfBatchEndpointId = 3;
+
+/// Endpoint ID of a Dart signal that reports app lifecycle events,
+/// which Rust receives with `lifecycle()`.
+const rinfLifecycleEndpointId = 4;
+
+/// Returns the prefix of symbols exported by the Rust library
+/// with the given namespace.
+/// This matches the prefix used by `write_interface!` in Rust.
+String getSymbolPrefix(String namespace) {
+  return namespace.isEmpty ? 'rinf_' : 'rinf_${namespace}_';
+}
 
 /// This contains a message from Rust.
 /// Optionally, a custom binary called `binary` can also be included.
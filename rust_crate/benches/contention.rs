@@ -0,0 +1,165 @@
+//! Compares the lock-free signal channel and isolate slot
+//! with the mutex-based versions they replaced,
+//! while many threads are sending at once.
+
+use allo_isolate::Isolate;
+use arc_swap::ArcSwapOption;
+use criterion::{Criterion, criterion_group, criterion_main};
+use rinf::signal_channel;
+use rinf::testing::block_on;
+use std::collections::VecDeque;
+use std::hint::black_box;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::scope;
+
+const SENDER_COUNT: usize = 8;
+const MESSAGES_PER_SENDER: usize = 10_000;
+const TOTAL_MESSAGES: usize = SENDER_COUNT * MESSAGES_PER_SENDER;
+
+/// Receives the given number of messages on the current thread,
+/// parking it while the receiving future is pending.
+/// Both channels are driven this way, so that they are compared
+/// with the same executor and waiting strategy.
+fn receive_all<F: Future>(count: usize, mut recv: impl FnMut() -> F) {
+  for _ in 0..count {
+    black_box(block_on(recv()));
+  }
+}
+
+/// The previous channel, which locked a mutex on every send and poll.
+mod mutex_channel {
+  use super::{Arc, Context, Mutex, Pin, Poll, VecDeque, Waker};
+
+  pub struct Channel<T> {
+    queue: VecDeque<T>,
+    waker: Option<Waker>,
+  }
+
+  pub type Shared<T> = Arc<Mutex<Channel<T>>>;
+
+  pub fn new<T>() -> Shared<T> {
+    Arc::new(Mutex::new(Channel {
+      queue: VecDeque::new(),
+      waker: None,
+    }))
+  }
+
+  pub fn send<T>(shared: &Shared<T>, msg: T) {
+    let Ok(mut guard) = shared.lock() else {
+      return;
+    };
+    guard.queue.push_back(msg);
+    if let Some(waker) = guard.waker.take() {
+      waker.wake();
+    }
+  }
+
+  pub fn recv<T>(shared: &Shared<T>) -> RecvFuture<'_, T> {
+    RecvFuture { shared }
+  }
+
+  pub struct RecvFuture<'a, T> {
+    shared: &'a Shared<T>,
+  }
+
+  impl<T> Future for RecvFuture<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+      let Ok(mut guard) = self.shared.lock() else {
+        return Poll::Ready(None);
+      };
+      match guard.queue.pop_front() {
+        Some(msg) => Poll::Ready(Some(msg)),
+        None => {
+          guard.waker = Some(cx.waker().to_owned());
+          Poll::Pending
+        }
+      }
+    }
+  }
+}
+
+fn bench_channel(c: &mut Criterion) {
+  let mut group = c.benchmark_group("channel");
+
+  group.bench_function("mutex", |b| {
+    b.iter(|| {
+      let shared = mutex_channel::new::<usize>();
+      scope(|s| {
+        for _ in 0..SENDER_COUNT {
+          let shared = &shared;
+          s.spawn(move || {
+            for i in 0..MESSAGES_PER_SENDER {
+              mutex_channel::send(shared, i);
+            }
+          });
+        }
+        receive_all(TOTAL_MESSAGES, || mutex_channel::recv(&shared));
+      });
+    });
+  });
+
+  group.bench_function("lock_free", |b| {
+    b.iter(|| {
+      let (sender, receiver) = signal_channel::<usize>();
+      scope(|s| {
+        for _ in 0..SENDER_COUNT {
+          let sender = &sender;
+          s.spawn(move || {
+            for i in 0..MESSAGES_PER_SENDER {
+              sender.send(i);
+            }
+          });
+        }
+        receive_all(TOTAL_MESSAGES, || receiver.recv());
+      });
+    });
+  });
+
+  group.finish();
+}
+
+fn bench_isolate_slot(c: &mut Criterion) {
+  let mut group = c.benchmark_group("isolate_slot");
+
+  group.bench_function("mutex", |b| {
+    let slot = Mutex::new(Some(Isolate::new(1)));
+    b.iter(|| {
+      scope(|s| {
+        for _ in 0..SENDER_COUNT {
+          s.spawn(|| {
+            for _ in 0..MESSAGES_PER_SENDER {
+              if let Ok(guard) = slot.lock() {
+                black_box(guard.as_ref());
+              }
+            }
+          });
+        }
+      });
+    });
+  });
+
+  group.bench_function("arc_swap", |b| {
+    let slot = ArcSwapOption::from_pointee(Isolate::new(1));
+    b.iter(|| {
+      scope(|s| {
+        for _ in 0..SENDER_COUNT {
+          s.spawn(|| {
+            for _ in 0..MESSAGES_PER_SENDER {
+              let guard = slot.load();
+              black_box(guard.as_ref());
+            }
+          });
+        }
+      });
+    });
+  });
+
+  group.finish();
+}
+
+criterion_group!(benches, bench_channel, bench_isolate_slot);
+criterion_main!(benches);
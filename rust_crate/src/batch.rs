@@ -0,0 +1,299 @@
+use crate::AppError;
+use crate::traits::GuardRecovery;
+use std::cell::Cell;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+#[cfg(all(feature = "headless", not(target_family = "wasm")))]
+use crate::headless::forward;
+#[cfg(not(target_family = "wasm"))]
+use crate::interface_os::{send_rust_signal_batch_real, send_rust_signal_real};
+#[cfg(target_family = "wasm")]
+use crate::interface_web::{
+  send_rust_signal_batch_real, send_rust_signal_real,
+};
+#[cfg(feature = "testing")]
+use crate::testing::capture;
+
+/// A Rust signal on its way to Dart,
+/// which might wait in the batch queue.
+pub struct QueuedSignal {
+  pub endpoint_id: u32,
+  pub message_bytes: Vec<u8>,
+  pub binary: Vec<u8>,
+}
+
+struct BatchState {
+  /// How many threads have a batch scope open.
+  open_scopes: usize,
+  /// How many flushes are delivering signals taken from the queue.
+  flushing: usize,
+  /// The time window for automatic batching, if enabled.
+  window: Option<Duration>,
+  /// Signals in the order they were sent.
+  queue: Vec<QueuedSignal>,
+  /// Whether a delayed flush for the time window is scheduled.
+  is_flush_scheduled: bool,
+}
+
+static BATCH_STATE: Mutex<BatchState> = Mutex::new(BatchState {
+  open_scopes: 0,
+  flushing: 0,
+  window: None,
+  queue: Vec::new(),
+  is_flush_scheduled: false,
+});
+
+/// Whether signals might need to go through the batch queue.
+/// This allows sending signals without locking
+/// when batching is not used at all.
+static IS_BATCHING: AtomicBool = AtomicBool::new(false);
+
+/// Held while signals are delivered outside of the batch queue,
+/// so that signals taken by one flush arrive
+/// before the ones sent after it.
+static DELIVERY_LOCK: Mutex<()> = Mutex::new(());
+
+thread_local! {
+  /// How many batch scopes are open on this thread.
+  static BATCH_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+impl BatchState {
+  fn update_flag(&self) {
+    let is_batching = self.open_scopes > 0
+      || self.flushing > 0
+      || self.window.is_some()
+      || !self.queue.is_empty();
+    IS_BATCHING.store(is_batching, Ordering::Release);
+  }
+}
+
+/// Runs the function while packing all Rust signals sent inside it
+/// into a single message to Dart,
+/// which is sent when the outermost batch scope ends.
+/// Dart receives the signals in the order they were sent.
+/// Only signals sent from the current thread are batched,
+/// while other threads keep sending theirs right away.
+/// This helps when sending many small signals at once,
+/// because each message wakes the Dart event loop.
+pub fn batch<F, R>(scope: F) -> R
+where
+  F: FnOnce() -> R,
+{
+  let depth = BATCH_DEPTH.get();
+  BATCH_DEPTH.set(depth + 1);
+  if depth == 0 {
+    let mut guard = BATCH_STATE.lock().recover();
+    guard.open_scopes += 1;
+    guard.update_flag();
+  }
+  // The scope is closed even if the function panics.
+  let _scope_closer = BatchScopeCloser;
+  scope()
+}
+
+struct BatchScopeCloser;
+
+impl Drop for BatchScopeCloser {
+  fn drop(&mut self) {
+    let depth = BATCH_DEPTH.get().saturating_sub(1);
+    BATCH_DEPTH.set(depth);
+    if depth > 0 {
+      return;
+    }
+    let mut guard = BATCH_STATE.lock().recover();
+    guard.open_scopes = guard.open_scopes.saturating_sub(1);
+    guard.update_flag();
+    match guard.window {
+      None => {
+        drop(guard);
+        let _ = flush();
+      }
+      // Signals left in the queue are sent with the time window.
+      Some(window) => {
+        if guard.queue.is_empty() || guard.is_flush_scheduled {
+          return;
+        }
+        guard.is_flush_scheduled = true;
+        drop(guard);
+        schedule_flush(window);
+      }
+    }
+  }
+}
+
+/// Enables or disables automatic batching with a time window.
+/// When enabled, Rust signals are packed into a single message to Dart
+/// that is sent once the window has passed since the first queued signal.
+/// A window of about one frame, such as 16 milliseconds,
+/// lets Dart receive all updates of a frame at once.
+/// Disabling it sends the queued signals immediately.
+pub fn set_batch_window(window: Option<Duration>) {
+  let mut guard = BATCH_STATE.lock().recover();
+  guard.window = window;
+  guard.update_flag();
+  drop(guard);
+  if window.is_none() {
+    let _ = flush();
+  }
+}
+
+/// Sends all queued Rust signals to Dart as a single message.
+/// This does nothing if no signals are queued.
+pub fn flush() -> Result<(), AppError> {
+  let _delivery_guard = DELIVERY_LOCK.lock().recover();
+  let mut guard = BATCH_STATE.lock().recover();
+  let mut queue = std::mem::take(&mut guard.queue);
+  guard.is_flush_scheduled = false;
+  // Direct sends should not overtake the taken signals,
+  // so the flag stays set until they are delivered.
+  guard.flushing += 1;
+  drop(guard);
+  // A single signal doesn't need to be packed.
+  let result = if queue.len() > 1 {
+    deliver_batch(queue)
+  } else if let Some(signal) = queue.pop() {
+    deliver(signal.endpoint_id, signal.message_bytes, signal.binary)
+  } else {
+    Ok(())
+  };
+  let mut guard = BATCH_STATE.lock().recover();
+  guard.flushing -= 1;
+  guard.update_flag();
+  result
+}
+
+/// Sends a Rust signal right away, or puts it in the batch queue
+/// if this thread has a batch scope open or the time window is enabled.
+pub fn send_or_queue(
+  endpoint_id: u32,
+  message_bytes: Vec<u8>,
+  binary: Vec<u8>,
+) -> Result<(), AppError> {
+  let depth = BATCH_DEPTH.get();
+  if depth == 0 && !IS_BATCHING.load(Ordering::Acquire) {
+    return deliver(endpoint_id, message_bytes, binary);
+  }
+
+  let mut guard = BATCH_STATE.lock().recover();
+  let signal = QueuedSignal {
+    endpoint_id,
+    message_bytes,
+    binary,
+  };
+  if depth > 0 {
+    guard.queue.push(signal);
+    guard.update_flag();
+    return Ok(());
+  }
+  if let Some(window) = guard.window {
+    guard.queue.push(signal);
+    guard.update_flag();
+    if !guard.is_flush_scheduled {
+      guard.is_flush_scheduled = true;
+      drop(guard);
+      schedule_flush(window);
+    }
+    return Ok(());
+  }
+  let has_open_scopes = guard.open_scopes > 0;
+  drop(guard);
+
+  if has_open_scopes {
+    // Batches of other threads are not sent early,
+    // but a flush in progress is waited for to keep the order.
+    let _delivery_guard = DELIVERY_LOCK.lock().recover();
+    return deliver(signal.endpoint_id, signal.message_bytes, signal.binary);
+  }
+  // Batching has just ended, so send the remaining signals first
+  // to keep the order.
+  flush()?;
+  deliver(signal.endpoint_id, signal.message_bytes, signal.binary)
+}
+
+/// Sends a Rust signal right away, after sending the queued signals.
+/// This is used for signals that should not wait, such as panic reports.
+pub fn send_immediately(
+  endpoint_id: u32,
+  message_bytes: Vec<u8>,
+  binary: Vec<u8>,
+) -> Result<(), AppError> {
+  if IS_BATCHING.load(Ordering::Acquire) {
+    flush()?;
+  }
+  deliver(endpoint_id, message_bytes, binary)
+}
+
+/// Sends a Rust signal to Dart,
+/// unless it's taken by the testing harness or the headless host.
+fn deliver(
+  endpoint_id: u32,
+  message_bytes: Vec<u8>,
+  binary: Vec<u8>,
+) -> Result<(), AppError> {
+  let Some(signal) = intercept(QueuedSignal {
+    endpoint_id,
+    message_bytes,
+    binary,
+  }) else {
+    return Ok(());
+  };
+  send_rust_signal_real(signal.endpoint_id, signal.message_bytes, signal.binary)
+}
+
+/// Sends multiple Rust signals to Dart at once,
+/// unless they're taken by the testing harness or the headless host.
+fn deliver_batch(signals: Vec<QueuedSignal>) -> Result<(), AppError> {
+  let signals: Vec<QueuedSignal> =
+    signals.into_iter().filter_map(intercept).collect();
+  if signals.is_empty() {
+    return Ok(());
+  }
+  send_rust_signal_batch_real(signals)
+}
+
+/// Passes a Rust signal to the destinations other than Dart, if any.
+/// Returns the signal back if it should be sent to Dart.
+fn intercept(signal: QueuedSignal) -> Option<QueuedSignal> {
+  #[cfg(feature = "testing")]
+  let signal = capture(signal)?;
+  #[cfg(all(feature = "headless", not(target_family = "wasm")))]
+  let signal = forward(signal)?;
+  Some(signal)
+}
+
+/// Flushes the batch queue after the time window on a separate thread.
+#[cfg(not(target_family = "wasm"))]
+fn schedule_flush(window: Duration) {
+  use std::thread::{sleep, spawn};
+  spawn(move || {
+    sleep(window);
+    let _ = flush();
+  });
+}
+
+/// Flushes the batch queue after the time window
+/// with `setTimeout` of JavaScript.
+#[cfg(target_family = "wasm")]
+fn schedule_flush(window: Duration) {
+  use js_sys::{Function, Reflect, global};
+  use wasm_bindgen::JsCast;
+  use wasm_bindgen::prelude::{Closure, JsValue};
+  let callback = Closure::once_into_js(|| {
+    let _ = flush();
+  });
+  let set_timeout = Reflect::get(&global(), &JsValue::from_str("setTimeout"))
+    .ok()
+    .and_then(|value| value.dyn_into::<Function>().ok());
+  match set_timeout {
+    Some(set_timeout) => {
+      let delay = JsValue::from_f64(window.as_secs_f64() * 1000.0);
+      let _ = set_timeout.call2(&JsValue::NULL, &callback, &delay);
+    }
+    None => {
+      let _ = flush();
+    }
+  }
+}
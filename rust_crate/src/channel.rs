@@ -1,15 +1,16 @@
-use crate::traits::GuardRecovery;
-use std::collections::VecDeque;
+use arc_swap::ArcSwap;
+use atomic_waker::AtomicWaker;
+use crossbeam_queue::SegQueue;
 use std::pin::Pin;
-use std::sync::{Arc, Mutex};
-use std::task::{Context, Poll, Waker};
+use std::sync::Arc;
+use std::task::{Context, Poll};
 
 /// The `SignalSender` is used to send messages into a shared message queue.
 /// It is clonable, and multiple senders can be created to send messages into
 /// the same queue. Each message is sent to a receiver, but only the currently
 /// active receiver can receive messages.
 pub struct SignalSender<T> {
-  inner: Arc<Mutex<SignalChannel<T>>>,
+  inner: Arc<SignalChannel<T>>,
 }
 
 /// The `SignalReceiver` is used to asynchronously receive messages from the
@@ -18,31 +19,42 @@ pub struct SignalSender<T> {
 /// it becomes the active receiver, and the previous receiver will no longer
 /// receive messages.
 pub struct SignalReceiver<T> {
-  inner: Arc<Mutex<SignalChannel<T>>>,
-  id: usize, // Each receiver has a unique ID
+  inner: Arc<SignalChannel<T>>,
+  slot: Arc<ReceiverSlot>, // Each receiver has its own slot
 }
 
 /// A channel holding a message queue and managing the current active receiver.
 /// Only the active receiver can receive messages.
+/// All fields are lock-free, so that many threads can send at once
+/// without waiting for each other.
 struct SignalChannel<T> {
-  queue: VecDeque<T>,
-  waker: Option<Waker>,
-  active_receiver_id: usize, // Track the active receiver by ID
+  queue: SegQueue<T>,
+  active_receiver: ArcSwap<ReceiverSlot>, // Track the active receiver
+}
+
+/// The place where a receiver registers its waker.
+/// Each receiver has its own slot,
+/// so that a receiver that was just replaced by a clone
+/// cannot overwrite the waker of the new active receiver.
+#[derive(Default)]
+struct ReceiverSlot {
+  waker: AtomicWaker,
 }
 
 impl<T> SignalSender<T> {
   /// Sends a message to the shared queue. If a receiver is waiting for a
-  /// message, it will be woken up. This method does not fail if the mutex
-  /// is poisoned but simply ignores the failure.
+  /// message, it will be woken up. This method never blocks.
   pub fn send(&self, msg: T) {
-    let mut guard = self.inner.lock().recover();
-
     // Enqueue the message.
-    guard.queue.push_back(msg);
-    // Wake up the previous receiver making it receive `None`, if any.
-    if let Some(waker) = guard.waker.take() {
-      waker.wake();
-    }
+    self.inner.queue.push(msg);
+    // Wake up the waiting receiver, if any.
+    self.inner.active_receiver.load().waker.wake();
+  }
+
+  /// Checks whether all receivers of this channel were dropped,
+  /// which means that sent messages will never be received.
+  pub(crate) fn is_closed(&self) -> bool {
+    Arc::strong_count(&self.inner) == 1
   }
 }
 
@@ -54,7 +66,7 @@ impl<T> SignalReceiver<T> {
   pub fn recv(&self) -> impl Future<Output = Option<T>> {
     RecvFuture {
       inner: self.inner.clone(),
-      receiver_id: self.id, // Pass the receiver's ID to the future
+      slot: self.slot.clone(), // Pass the receiver's slot to the future
     }
   }
 }
@@ -65,16 +77,15 @@ impl<T> Clone for SignalReceiver<T> {
   /// original receiver will no longer receive messages after this clone.
   /// This ensures only the most recent receiver can access the message queue.
   fn clone(&self) -> Self {
-    let mut guard = self.inner.lock().recover();
-    let new_receiver = SignalReceiver {
+    // Make a new slot the active one.
+    let slot = Arc::new(ReceiverSlot::default());
+    let previous_slot = self.inner.active_receiver.swap(slot.clone());
+    // Wake up the previous receiver making it receive `None`, if any.
+    previous_slot.waker.wake();
+    SignalReceiver {
       inner: self.inner.clone(),
-      id: guard.active_receiver_id + 1, // Increment ID for new receiver
-    };
-    guard.active_receiver_id = new_receiver.id;
-    if let Some(waker) = guard.waker.take() {
-      waker.wake();
+      slot,
     }
-    new_receiver
   }
 }
 
@@ -82,8 +93,8 @@ impl<T> Clone for SignalReceiver<T> {
 /// message. This future is only completed when the active receiver receives
 /// a message from the queue.
 struct RecvFuture<T> {
-  inner: Arc<Mutex<SignalChannel<T>>>,
-  receiver_id: usize, // Track which receiver is polling
+  inner: Arc<SignalChannel<T>>,
+  slot: Arc<ReceiverSlot>, // Track which receiver is polling
 }
 
 impl<T> Future for RecvFuture<T> {
@@ -94,28 +105,46 @@ impl<T> Future for RecvFuture<T> {
   /// a message is sent. If this receiver is not the active receiver, it will
   /// return `None`.
   fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-    let mut guard = self.inner.lock().recover();
-
     // Only allow the current active receiver to receive messages.
-    if guard.active_receiver_id == self.receiver_id {
-      match guard.queue.pop_front() {
-        Some(msg) => {
-          // Check if more messages are in the queue.
-          if !guard.queue.is_empty() {
-            // If so, wake the current task immediately.
-            cx.waker().wake_by_ref();
-          }
-          Poll::Ready(Some(msg))
-        }
-        None => {
-          guard.waker = Some(cx.waker().to_owned());
-          Poll::Pending
-        }
-      }
-    } else {
-      // Return None if this receiver is not the current active one.
-      Poll::Ready(None)
+    if !self.is_active() {
+      return Poll::Ready(None);
+    }
+    if let Some(msg) = self.pop_message(cx) {
+      return Poll::Ready(Some(msg));
+    }
+
+    // Register the waker before checking again,
+    // so that a message sent in between is not missed.
+    // If a clone replaced this receiver in between,
+    // the waker is removed and nothing is taken from the queue.
+    self.slot.waker.register(cx.waker());
+    if !self.is_active() {
+      self.slot.waker.take();
+      return Poll::Ready(None);
+    }
+    match self.pop_message(cx) {
+      Some(msg) => Poll::Ready(Some(msg)),
+      None => Poll::Pending,
+    }
+  }
+}
+
+impl<T> RecvFuture<T> {
+  /// Checks whether the receiver of this future is the active one.
+  fn is_active(&self) -> bool {
+    let active_receiver = self.inner.active_receiver.load();
+    Arc::ptr_eq(&active_receiver, &self.slot)
+  }
+
+  /// Takes the next message from the queue, if any.
+  fn pop_message(&self, cx: &mut Context<'_>) -> Option<T> {
+    let msg = self.inner.queue.pop()?;
+    // Check if more messages are in the queue.
+    if !self.inner.queue.is_empty() {
+      // If so, wake the current task immediately.
+      cx.waker().wake_by_ref();
     }
+    Some(msg)
   }
 }
 
@@ -125,20 +154,19 @@ impl<T> Future for RecvFuture<T> {
 /// are created by cloning the original receiver.
 #[doc(hidden)]
 pub fn signal_channel<T>() -> (SignalSender<T>, SignalReceiver<T>) {
-  let start_receiver_id = 0;
+  let start_slot = Arc::new(ReceiverSlot::default());
 
-  let channel = Arc::new(Mutex::new(SignalChannel {
-    queue: VecDeque::new(),
-    waker: None,
-    active_receiver_id: start_receiver_id,
-  }));
+  let channel = Arc::new(SignalChannel {
+    queue: SegQueue::new(),
+    active_receiver: ArcSwap::new(start_slot.clone()),
+  });
 
   let sender = SignalSender {
     inner: channel.clone(),
   };
   let receiver = SignalReceiver {
     inner: channel,
-    id: start_receiver_id,
+    slot: start_slot,
   };
   (sender, receiver)
 }
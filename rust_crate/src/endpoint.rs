@@ -0,0 +1,99 @@
+use crate::debug_print;
+#[cfg(all(feature = "inspector", not(target_family = "wasm")))]
+use crate::inspector::{Direction, mirror};
+#[cfg(all(feature = "recording", not(target_family = "wasm")))]
+use crate::recording::{RecordedDirection, record};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// An entry of the registry of Dart signal endpoints.
+/// Derive macros submit one entry for each `DartSignal` at compile time,
+/// so that a single FFI function can dispatch all Dart signals.
+#[doc(hidden)]
+pub struct DartSignalEndpoint {
+  /// The endpoint ID assigned by `rinf gen`.
+  pub id: u32,
+  /// The name of the signal type, used only for debugging.
+  pub name: &'static str,
+  /// Decodes the message and sends it to the signal channel.
+  /// The binary is moved into the signal without being copied.
+  pub handler: fn(&[u8], Vec<u8>),
+}
+
+inventory::collect!(DartSignalEndpoint);
+
+// Endpoint IDs below 16 are reserved for Rinf itself,
+// and `rinf gen` assigns IDs to signals starting from 16.
+
+/// Endpoint ID of Rust output from `debug_print!`.
+#[doc(hidden)]
+pub const OUT_ENDPOINT_ID: u32 = 0;
+
+/// Endpoint ID of log records.
+pub(crate) const LOG_ENDPOINT_ID: u32 = 1;
+
+/// Endpoint ID of panic reports.
+pub(crate) const PANIC_ENDPOINT_ID: u32 = 2;
+
+/// Endpoint ID of a message that packs multiple Rust signals.
+pub(crate) const BATCH_ENDPOINT_ID: u32 = 3;
+
+/// Endpoint ID of app lifecycle events from Dart.
+pub(crate) const LIFECYCLE_ENDPOINT_ID: u32 = 4;
+
+/// Provides the endpoint ID of a Dart signal type.
+/// This is implemented by the derive macros of Dart signals.
+#[doc(hidden)]
+pub trait DartSignalId {
+  const ENDPOINT_ID: u32;
+}
+
+/// Provides the endpoint ID of a Rust signal type.
+/// This is implemented by the derive macros of Rust signals.
+#[doc(hidden)]
+pub trait RustSignalId {
+  const ENDPOINT_ID: u32;
+}
+
+/// Dart signal endpoints indexed by their IDs.
+static DART_SIGNAL_ENDPOINTS: LazyLock<
+  HashMap<u32, &'static DartSignalEndpoint>,
+> = LazyLock::new(|| {
+  let mut endpoints = HashMap::new();
+  for endpoint in inventory::iter::<DartSignalEndpoint> {
+    if let Some(existing) = endpoints.insert(endpoint.id, endpoint) {
+      debug_print!(
+        "Dart signals `{}` and `{}` have the same endpoint ID {}",
+        existing.name,
+        endpoint.name,
+        endpoint.id,
+      );
+    }
+  }
+  endpoints
+});
+
+/// Passes a Dart signal to the endpoint with the given ID.
+/// The binary is owned so that it reaches the receiver without a copy.
+#[doc(hidden)]
+pub fn send_dart_signal(
+  endpoint_id: u32,
+  message_bytes: &[u8],
+  binary: Vec<u8>,
+) {
+  #[cfg(all(feature = "inspector", not(target_family = "wasm")))]
+  mirror(Direction::DartToRust, endpoint_id, message_bytes, &binary);
+  #[cfg(all(feature = "recording", not(target_family = "wasm")))]
+  record(
+    RecordedDirection::DartToRust,
+    endpoint_id,
+    message_bytes,
+    &binary,
+  );
+  match DART_SIGNAL_ENDPOINTS.get(&endpoint_id) {
+    Some(endpoint) => (endpoint.handler)(message_bytes, binary),
+    None => {
+      debug_print!("No Dart signal endpoint has the ID {}", endpoint_id);
+    }
+  }
+}
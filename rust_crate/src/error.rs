@@ -7,6 +7,10 @@ pub enum AppError {
   CannotEncodeMessage,
   CannotDecodeMessage,
   NoBindings,
+  TaskPanicked,
+  CannotOpenSocket,
+  CannotAccessRecording,
+  CannotStartWorker,
 }
 
 impl Error for AppError {}
@@ -26,6 +30,18 @@ impl Display for AppError {
       Self::NoBindings => {
         write!(f, "Rinf bindings are not ready")
       }
+      Self::TaskPanicked => {
+        write!(f, "The task panicked")
+      }
+      Self::CannotOpenSocket => {
+        write!(f, "Could not open the socket for the headless host")
+      }
+      Self::CannotAccessRecording => {
+        write!(f, "Could not read or write the signal recording")
+      }
+      Self::CannotStartWorker => {
+        write!(f, "Could not start the web worker for Rust logic")
+      }
     }
   }
 }
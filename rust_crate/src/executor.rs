@@ -0,0 +1,57 @@
+//! Runs futures on the current thread
+//! when there's no async runtime to drive them.
+
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{Thread, current, park};
+
+#[cfg(feature = "testing")]
+use std::thread::park_timeout;
+#[cfg(feature = "testing")]
+use std::time::{Duration, Instant};
+
+/// Wakes the thread that is waiting for a future.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+  fn wake(self: Arc<Self>) {
+    self.0.unpark();
+  }
+}
+
+/// Runs a future to completion on the current thread,
+/// parking the thread while the future is pending.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+  let waker = Waker::from(Arc::new(ThreadWaker(current())));
+  let mut cx = Context::from_waker(&waker);
+  let mut future = pin!(future);
+  loop {
+    if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+      return output;
+    }
+    park();
+  }
+}
+
+/// Runs a future on the current thread,
+/// returning `None` if it doesn't complete in time.
+#[cfg(feature = "testing")]
+pub fn block_on_timeout<F: Future>(
+  future: F,
+  timeout: Duration,
+) -> Option<F::Output> {
+  let waker = Waker::from(Arc::new(ThreadWaker(current())));
+  let mut cx = Context::from_waker(&waker);
+  let mut future = pin!(future);
+  let deadline = Instant::now() + timeout;
+  loop {
+    if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+      return Some(output);
+    }
+    park_timeout(deadline.saturating_duration_since(Instant::now()));
+    if Instant::now() >= deadline {
+      return None;
+    }
+  }
+}
@@ -0,0 +1,209 @@
+//! Runs the Rust logic without Flutter,
+//! exchanging signals with a client over a local socket.
+//!
+//! Each frame consists of a header and two byte sections.
+//! The header contains the endpoint ID as a little-endian `u32`,
+//! followed by the sizes of the message bytes and the binary
+//! as little-endian `u64`s.
+//! Output from `debug_print!` is sent with the endpoint ID 0,
+//! carrying the text as UTF-8 in the binary.
+
+use crate::AppError;
+use crate::batch::QueuedSignal;
+#[cfg(feature = "inspector")]
+use crate::inspector::start_inspector;
+use crate::panic::install_panic_hook;
+use crate::send_dart_signal;
+use crate::shutdown::SHUTDOWN_EVENTS;
+use crate::traits::GuardRecovery;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpListener;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::spawn;
+
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+/// Size of the frame header in bytes.
+const HEADER_SIZE: usize = 20;
+
+/// The stream that Rust signals are written to.
+static CLIENT_WRITER: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+static IS_HOSTING: AtomicBool = AtomicBool::new(false);
+
+/// The local socket that the headless host listens on.
+pub enum HeadlessAddress {
+  /// A TCP address, such as `127.0.0.1:7878`.
+  Tcp(String),
+  /// The path of a Unix domain socket.
+  #[cfg(unix)]
+  Unix(PathBuf),
+}
+
+impl HeadlessAddress {
+  /// Parses an address, treating the `unix:` prefix as a socket path.
+  pub fn parse(address: &str) -> Self {
+    #[cfg(unix)]
+    if let Some(path) = address.strip_prefix("unix:") {
+      return Self::Unix(PathBuf::from(path));
+    }
+    Self::Tcp(address.to_owned())
+  }
+}
+
+/// Runs the main function given to `start_rust_logic` without Flutter.
+/// This waits for a single client to connect to the address,
+/// then forwards all Rust signals to the client
+/// and passes frames from the client to Dart signal receivers.
+/// When the client disconnects, the Rust logic is shut down
+/// as if the Dart side has exited, and this function returns.
+pub fn run_headless<F, T>(
+  main_fn: F,
+  address: HeadlessAddress,
+) -> Result<(), AppError>
+where
+  F: Fn() -> T + Send + 'static,
+{
+  // Mirror signals to `rinf monitor` in debug mode.
+  #[cfg(feature = "inspector")]
+  start_inspector();
+
+  // Report panics in the CLI and to the client.
+  install_panic_hook();
+
+  let (reader, writer) = accept_client(address)?;
+  let mut guard = CLIENT_WRITER.lock().recover();
+  guard.replace(writer);
+  drop(guard);
+  IS_HOSTING.store(true, Ordering::Release);
+
+  // Run the Rust logic on a separate thread,
+  // just like when it's started by Dart.
+  SHUTDOWN_EVENTS.reset();
+  spawn(move || {
+    main_fn();
+    SHUTDOWN_EVENTS.rust_stopped.set();
+  });
+
+  // Pass incoming frames through the same entry point as FFI.
+  read_frames(reader);
+
+  // The client has disconnected, so stop the Rust logic.
+  SHUTDOWN_EVENTS.stop_dart();
+  SHUTDOWN_EVENTS.wait_rust_stopped();
+  IS_HOSTING.store(false, Ordering::Release);
+  CLIENT_WRITER.lock().recover().take();
+
+  Ok(())
+}
+
+type ClientStreams = (Box<dyn Read + Send>, Box<dyn Write + Send>);
+
+/// Waits for a client and splits its stream into a reader and a writer.
+fn accept_client(address: HeadlessAddress) -> Result<ClientStreams, AppError> {
+  match address {
+    HeadlessAddress::Tcp(address) => {
+      let listener =
+        TcpListener::bind(address).map_err(|_| AppError::CannotOpenSocket)?;
+      let (stream, _) =
+        listener.accept().map_err(|_| AppError::CannotOpenSocket)?;
+      let _ = stream.set_nodelay(true);
+      let writer =
+        stream.try_clone().map_err(|_| AppError::CannotOpenSocket)?;
+      Ok((Box::new(stream), Box::new(writer)))
+    }
+    #[cfg(unix)]
+    HeadlessAddress::Unix(path) => {
+      // A socket file left by a previous run prevents binding.
+      let _ = std::fs::remove_file(&path);
+      let listener =
+        UnixListener::bind(&path).map_err(|_| AppError::CannotOpenSocket)?;
+      let (stream, _) =
+        listener.accept().map_err(|_| AppError::CannotOpenSocket)?;
+      let writer =
+        stream.try_clone().map_err(|_| AppError::CannotOpenSocket)?;
+      Ok((Box::new(stream), Box::new(writer)))
+    }
+  }
+}
+
+/// Reads frames from the client until it disconnects
+/// or sends a malformed frame.
+fn read_frames(mut reader: Box<dyn Read + Send>) {
+  let mut header = [0u8; HEADER_SIZE];
+  loop {
+    match reader.read_exact(&mut header) {
+      Ok(()) => {}
+      Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+      Err(_) => return,
+    }
+    let (endpoint_id, message_size, binary_size) = parse_header(&header);
+    let Some(message_bytes) = read_section(&mut reader, message_size) else {
+      return;
+    };
+    let Some(binary) = read_section(&mut reader, binary_size) else {
+      return;
+    };
+    send_dart_signal(endpoint_id, &message_bytes, binary);
+  }
+}
+
+/// Reads a byte section of a frame.
+/// Memory grows only as bytes actually arrive,
+/// so a bogus size in the header cannot allocate a huge buffer.
+/// Returns `None` if the client disconnects before the section ends.
+fn read_section(
+  reader: &mut Box<dyn Read + Send>,
+  size: u64,
+) -> Option<Vec<u8>> {
+  let mut section = Vec::new();
+  reader.take(size).read_to_end(&mut section).ok()?;
+  (section.len() as u64 == size).then_some(section)
+}
+
+fn parse_header(header: &[u8; HEADER_SIZE]) -> (u32, u64, u64) {
+  let mut endpoint_id = [0u8; 4];
+  let mut message_size = [0u8; 8];
+  let mut binary_size = [0u8; 8];
+  endpoint_id.copy_from_slice(&header[0..4]);
+  message_size.copy_from_slice(&header[4..12]);
+  binary_size.copy_from_slice(&header[12..20]);
+  (
+    u32::from_le_bytes(endpoint_id),
+    u64::from_le_bytes(message_size),
+    u64::from_le_bytes(binary_size),
+  )
+}
+
+/// Writes a Rust signal to the client if the headless host is running.
+/// Returns the signal back if it should be sent to Dart instead.
+pub(crate) fn forward(signal: QueuedSignal) -> Option<QueuedSignal> {
+  if !IS_HOSTING.load(Ordering::Acquire) {
+    return Some(signal);
+  }
+  // Output from `debug_print!` is written to the client
+  // like any other Rust signal, just as Dart receives it.
+  let QueuedSignal {
+    endpoint_id,
+    message_bytes,
+    binary,
+  } = signal;
+  let mut frame =
+    Vec::with_capacity(HEADER_SIZE + message_bytes.len() + binary.len());
+  frame.extend_from_slice(&endpoint_id.to_le_bytes());
+  frame.extend_from_slice(&(message_bytes.len() as u64).to_le_bytes());
+  frame.extend_from_slice(&(binary.len() as u64).to_le_bytes());
+  frame.extend_from_slice(&message_bytes);
+  frame.extend_from_slice(&binary);
+  let mut guard = CLIENT_WRITER.lock().recover();
+  if let Some(writer) = guard.as_mut() {
+    // Signals are dropped when the client is gone,
+    // just like when Dart has exited.
+    let _ = writer.write_all(&frame);
+  }
+  None
+}
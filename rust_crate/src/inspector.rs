@@ -0,0 +1,129 @@
+//! Mirrors signal traffic to a local WebSocket in debug mode,
+//! so that `rinf monitor` can show signals crossing the bridge.
+//!
+//! Each WebSocket message is a binary message
+//! that contains a bincode-encoded tuple of the timestamp
+//! in microseconds since the Unix epoch, the direction,
+//! the endpoint ID, the message bytes, and the size of the binary.
+//! The binary itself is not mirrored because it can be very large.
+//! When clients can't keep up, signals are dropped from the mirror
+//! instead of slowing down the app.
+
+use crate::traits::GuardRecovery;
+use crate::{debug_print, serialize};
+use std::env::var;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::sync::{Mutex, OnceLock};
+use std::thread::spawn;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tungstenite::{Message, WebSocket, accept};
+
+/// The default port of the inspector,
+/// which can be changed with the `RINF_INSPECTOR_PORT` environment variable.
+const DEFAULT_PORT: u16 = 9339;
+
+/// How many mirrored signals can wait to be broadcast.
+/// Signals are dropped when this is full,
+/// so that slow clients don't make memory grow.
+const QUEUE_CAPACITY: usize = 1024;
+
+/// How long sending to a client can take
+/// before the client is disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Which way a mirrored signal was going.
+#[derive(Clone, Copy)]
+pub enum Direction {
+  DartToRust = 0,
+  RustToDart = 1,
+}
+
+static MIRROR_SENDER: OnceLock<SyncSender<Vec<u8>>> = OnceLock::new();
+
+static CLIENTS: Mutex<Vec<WebSocket<TcpStream>>> = Mutex::new(Vec::new());
+
+/// Whether any inspector client is connected.
+/// Signals are not encoded at all when this is `false`.
+static HAS_CLIENTS: AtomicBool = AtomicBool::new(false);
+
+/// Starts the inspector in debug mode.
+/// Calling this more than once has no effect.
+pub fn start_inspector() {
+  if !cfg!(debug_assertions) || MIRROR_SENDER.get().is_some() {
+    return;
+  }
+  let port = var("RINF_INSPECTOR_PORT")
+    .ok()
+    .and_then(|text| text.parse().ok())
+    .unwrap_or(DEFAULT_PORT);
+  let listener = match TcpListener::bind(("127.0.0.1", port)) {
+    Ok(inner) => inner,
+    Err(err) => {
+      debug_print!("Signal inspector could not start on port {port}: {err}");
+      return;
+    }
+  };
+  let (sender, receiver) = sync_channel(QUEUE_CAPACITY);
+  if MIRROR_SENDER.set(sender).is_err() {
+    return;
+  }
+  spawn(move || accept_clients(listener));
+  spawn(move || broadcast_messages(receiver));
+}
+
+fn accept_clients(listener: TcpListener) {
+  for stream in listener.incoming().flatten() {
+    // A stalled client should not hold up the others.
+    if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+      continue;
+    }
+    let Ok(web_socket) = accept(stream) else {
+      continue;
+    };
+    let mut guard = CLIENTS.lock().recover();
+    guard.push(web_socket);
+    HAS_CLIENTS.store(true, Ordering::Release);
+  }
+}
+
+fn broadcast_messages(receiver: Receiver<Vec<u8>>) {
+  for encoded in receiver {
+    let mut guard = CLIENTS.lock().recover();
+    // Disconnected clients are removed.
+    guard.retain_mut(|web_socket| {
+      web_socket.send(Message::binary(encoded.clone())).is_ok()
+    });
+    HAS_CLIENTS.store(!guard.is_empty(), Ordering::Release);
+  }
+}
+
+/// Sends a copy of a signal to inspector clients, if any.
+pub fn mirror(
+  direction: Direction,
+  endpoint_id: u32,
+  message_bytes: &[u8],
+  binary: &[u8],
+) {
+  if !HAS_CLIENTS.load(Ordering::Acquire) {
+    return;
+  }
+  let Some(sender) = MIRROR_SENDER.get() else {
+    return;
+  };
+  let timestamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_micros() as u64)
+    .unwrap_or_default();
+  let encoded = serialize(&(
+    timestamp,
+    direction as u8,
+    endpoint_id,
+    message_bytes,
+    binary.len() as u64,
+  ));
+  if let Ok(encoded) = encoded {
+    let _ = sender.try_send(encoded);
+  }
+}
@@ -1,9 +1,19 @@
 use crate::AppError;
+use crate::batch::send_or_queue;
+#[cfg(all(feature = "inspector", not(target_family = "wasm")))]
+use crate::inspector::{Direction, mirror};
+#[cfg(all(feature = "recording", not(target_family = "wasm")))]
+use crate::recording::{RecordedDirection, record};
+use std::sync::OnceLock;
 
 #[cfg(not(target_family = "wasm"))]
-use crate::interface_os::{send_rust_signal_real, start_rust_logic_real};
+use crate::interface_os::{
+  prepare_isolate_real, start_rust_logic_real, stop_rust_logic_real,
+};
 #[cfg(target_family = "wasm")]
-use crate::interface_web::{send_rust_signal_real, start_rust_logic_real};
+use crate::interface_web::{
+  run_worker_main_real, start_rust_logic_in_worker_real, start_rust_logic_real,
+};
 
 /// This contains a message from Dart.
 /// Optionally, a custom binary called `binary` can also be included.
@@ -17,6 +27,40 @@ pub struct DartSignalPack<T> {
   pub binary: Vec<u8>,
 }
 
+#[doc(hidden)]
+#[cfg(not(target_family = "wasm"))]
+pub use allo_isolate::ffi::DartPostCObjectFnType;
+
+/// The namespace of this library, given to `write_interface!`.
+static NAMESPACE: OnceLock<&'static str> = OnceLock::new();
+
+/// Stores the namespace of this library.
+#[doc(hidden)]
+pub fn set_namespace(namespace: &'static str) {
+  let _ = NAMESPACE.set(namespace);
+}
+
+/// Returns the namespace of this library given to `write_interface!`.
+/// This is an empty string if no namespace was provided.
+pub fn get_namespace() -> &'static str {
+  NAMESPACE.get().copied().unwrap_or_default()
+}
+
+/// Stores the Dart isolate port that receives Rust signals.
+#[doc(hidden)]
+#[cfg(not(target_family = "wasm"))]
+pub fn prepare_isolate(store_post_object: DartPostCObjectFnType, port: i64) {
+  prepare_isolate_real(store_post_object, port)
+}
+
+/// Stops the async runtime in Rust,
+/// blocking until it's completely dropped.
+#[doc(hidden)]
+#[cfg(not(target_family = "wasm"))]
+pub fn stop_rust_logic() {
+  stop_rust_logic_real()
+}
+
 /// Runs the async main function in Rust.
 /// On native platforms, futures usually implement the `Send` trait
 /// to be safely sent between threads.
@@ -43,12 +87,45 @@ where
   start_rust_logic_real(main_fn)
 }
 
+/// Runs the async main function in Rust inside a dedicated web worker,
+/// which shares the WebAssembly memory with the main thread.
+/// This keeps heavy Rust work from freezing Flutter's UI,
+/// as the main thread only passes signals.
+#[doc(hidden)]
+#[cfg(target_family = "wasm")]
+pub fn start_rust_logic_in_worker<F, T>(main_fn: F) -> Result<(), AppError>
+where
+  F: Fn() -> T + Send + 'static,
+{
+  start_rust_logic_in_worker_real(main_fn)
+}
+
+/// Runs the main function handed over to a web worker
+/// by `start_rust_logic_in_worker`.
+#[doc(hidden)]
+#[cfg(target_family = "wasm")]
+pub fn run_worker_main(worker_id: usize) {
+  run_worker_main_real(worker_id)
+}
+
 /// Send a signal to Dart.
+/// The endpoint ID is the one assigned by `rinf gen`,
+/// which is written in `rinf_endpoints.txt`.
+/// The signal is queued instead if batching is active.
 #[doc(hidden)]
 pub fn send_rust_signal(
-  endpoint: &str,
+  endpoint_id: u32,
   message_bytes: Vec<u8>,
   binary: Vec<u8>,
 ) -> Result<(), AppError> {
-  send_rust_signal_real(endpoint, message_bytes, binary)
+  #[cfg(all(feature = "inspector", not(target_family = "wasm")))]
+  mirror(Direction::RustToDart, endpoint_id, &message_bytes, &binary);
+  #[cfg(all(feature = "recording", not(target_family = "wasm")))]
+  record(
+    RecordedDirection::RustToDart,
+    endpoint_id,
+    &message_bytes,
+    &binary,
+  );
+  send_or_queue(endpoint_id, message_bytes, binary)
 }
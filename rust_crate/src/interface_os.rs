@@ -1,26 +1,31 @@
 use crate::AppError;
+use crate::batch::QueuedSignal;
+use crate::endpoint::BATCH_ENDPOINT_ID;
+#[cfg(feature = "inspector")]
+use crate::inspector::start_inspector;
+use crate::panic::install_panic_hook;
 use crate::shutdown::SHUTDOWN_EVENTS;
-use crate::traits::GuardRecovery;
-use allo_isolate::ffi::DartPostCObjectFnType;
+use allo_isolate::ffi::{DartCObject, DartPostCObjectFnType};
 use allo_isolate::{
   IntoDart, Isolate, ZeroCopyBuffer, store_dart_post_cobject,
 };
+use arc_swap::ArcSwapOption;
 use os_thread_local::ThreadLocal;
-use std::sync::Mutex;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 use std::thread::spawn;
 
-static DART_ISOLATE: Mutex<Option<Isolate>> = Mutex::new(None);
+/// The Dart isolate that receives Rust signals.
+/// It's swapped atomically, so that sending threads can read it
+/// without waiting for each other.
+static DART_ISOLATE: ArcSwapOption<Isolate> = ArcSwapOption::const_empty();
 
-#[unsafe(no_mangle)]
-extern "C" fn rinf_prepare_isolate_extern(
+pub fn prepare_isolate_real(
   store_post_object: DartPostCObjectFnType,
   port: i64,
 ) {
   unsafe { store_dart_post_cobject(store_post_object) }
   let dart_isolate = Isolate::new(port);
-  let mut guard = DART_ISOLATE.lock().recover();
-  guard.replace(dart_isolate);
+  DART_ISOLATE.store(Some(Arc::new(dart_isolate)));
 }
 
 // We use `os_thread_local` so that when the program fails
@@ -37,8 +42,8 @@ pub struct ShutdownDropper;
 
 impl Drop for ShutdownDropper {
   fn drop(&mut self) {
-    SHUTDOWN_EVENTS.dart_stopped.set();
-    SHUTDOWN_EVENTS.rust_stopped.wait();
+    SHUTDOWN_EVENTS.stop_dart();
+    SHUTDOWN_EVENTS.wait_rust_stopped();
   }
 }
 
@@ -46,30 +51,12 @@ pub fn start_rust_logic_real<F, T>(main_fn: F) -> Result<(), AppError>
 where
   F: Fn() -> T + Send + 'static,
 {
-  // Enable console output for panics.
-  #[cfg(debug_assertions)]
-  {
-    use crate::debug_print;
-    use std::panic::set_hook;
-    #[cfg(not(feature = "backtrace"))]
-    {
-      set_hook(Box::new(|panic_info| {
-        debug_print!("A panic occurred in Rust.\n{}", panic_info);
-      }));
-    }
-    #[cfg(feature = "backtrace")]
-    {
-      use backtrace::Backtrace;
-      set_hook(Box::new(|panic_info| {
-        let backtrace = Backtrace::new();
-        debug_print!(
-          "A panic occurred in Rust.\n{}\n{:?}",
-          panic_info,
-          backtrace
-        );
-      }));
-    }
-  }
+  // Mirror signals to `rinf monitor` in debug mode.
+  #[cfg(feature = "inspector")]
+  start_inspector();
+
+  // Report panics in the CLI and to Dart.
+  install_panic_hook();
 
   // Prepare the shutdown dropper that will notify the Rust async runtime
   // after Dart thread has exited.
@@ -83,11 +70,10 @@ where
     // to terminate the previous Rust async runtime threads.
     // After Dart's hot restart or reopening the app,
     // Previous Rust async runtime can be still running.
-    SHUTDOWN_EVENTS.dart_stopped.set();
+    SHUTDOWN_EVENTS.stop_dart();
 
     // Clear shutdown events to prepare for a fresh start.
-    SHUTDOWN_EVENTS.dart_stopped.clear();
-    SHUTDOWN_EVENTS.rust_stopped.clear();
+    SHUTDOWN_EVENTS.reset();
 
     // Execute the long-running function that will block the thread
     // for the entire lifecycle of the app.
@@ -103,44 +89,77 @@ where
   Ok(())
 }
 
-#[unsafe(no_mangle)]
-extern "C" fn rinf_stop_rust_logic_extern() {
-  SHUTDOWN_EVENTS.dart_stopped.set();
-  SHUTDOWN_EVENTS.rust_stopped.wait();
+pub fn stop_rust_logic_real() {
+  SHUTDOWN_EVENTS.stop_dart();
+  SHUTDOWN_EVENTS.wait_rust_stopped();
 }
 
 pub fn send_rust_signal_real(
-  endpoint: &str,
+  endpoint_id: u32,
   message_bytes: Vec<u8>,
   binary: Vec<u8>,
 ) -> Result<(), AppError> {
   // When `DART_ISOLATE` is not initialized, just return the error.
   // This can happen when running test code in Rust.
-  let guard = DART_ISOLATE.lock().recover();
+  let guard = DART_ISOLATE.load();
   let dart_isolate = guard.as_ref().ok_or(AppError::NoDartIsolate)?;
 
-  // If a `Vec<u8>` is empty, we can't just simply send it to Dart
-  // because panic can occur from null pointers.
-  // Instead, we will reconstruct the empty vector from the Dart side.
-  let message_filled = !message_bytes.is_empty();
-  let binary_filled = !binary.is_empty();
+  dart_isolate.post(encode_signal(endpoint_id, message_bytes, binary));
+
+  Ok(())
+}
 
+/// Sends multiple Rust signals to Dart with a single post.
+/// Dart unpacks them in order.
+pub fn send_rust_signal_batch_real(
+  signals: Vec<QueuedSignal>,
+) -> Result<(), AppError> {
+  let guard = DART_ISOLATE.load();
+  let dart_isolate = guard.as_ref().ok_or(AppError::NoDartIsolate)?;
+
+  let encoded: Vec<DartCObject> = signals
+    .into_iter()
+    .map(|signal| {
+      encode_signal(signal.endpoint_id, signal.message_bytes, signal.binary)
+    })
+    .collect();
   dart_isolate.post(
     vec![
-      endpoint.into_dart(),
-      if message_filled {
-        ZeroCopyBuffer(message_bytes).into_dart()
-      } else {
-        ().into_dart()
-      },
-      if binary_filled {
-        ZeroCopyBuffer(binary).into_dart()
-      } else {
-        ().into_dart()
-      },
+      i64::from(BATCH_ENDPOINT_ID).into_dart(),
+      encoded.into_dart(),
+      ().into_dart(),
     ]
     .into_dart(),
   );
 
   Ok(())
 }
+
+/// Converts a signal into the list of its endpoint ID,
+/// message bytes, and binary that Dart receives.
+fn encode_signal(
+  endpoint_id: u32,
+  message_bytes: Vec<u8>,
+  binary: Vec<u8>,
+) -> DartCObject {
+  // If a `Vec<u8>` is empty, we can't just simply send it to Dart
+  // because panic can occur from null pointers.
+  // Instead, we will reconstruct the empty vector from the Dart side.
+  let message_filled = !message_bytes.is_empty();
+  let binary_filled = !binary.is_empty();
+
+  vec![
+    i64::from(endpoint_id).into_dart(),
+    if message_filled {
+      ZeroCopyBuffer(message_bytes).into_dart()
+    } else {
+      ().into_dart()
+    },
+    if binary_filled {
+      ZeroCopyBuffer(binary).into_dart()
+    } else {
+      ().into_dart()
+    },
+  ]
+  .into_dart()
+}
@@ -1,24 +1,29 @@
 use crate::AppError;
-use crate::shutdown::{SHUTDOWN_EVENTS, ShutdownEventsLock};
-use js_sys::Uint8Array;
-use wasm_bindgen::prelude::{JsValue, wasm_bindgen};
+use crate::batch::{QueuedSignal, flush};
+use crate::debug_print;
+use crate::endpoint::BATCH_ENDPOINT_ID;
+use crate::interface::get_namespace;
+use crate::panic::install_panic_hook;
+use crate::shutdown::{SHUTDOWN_EVENTS, hooks_finished};
+use crate::traits::GuardRecovery;
+use crossbeam_queue::SegQueue;
+use js_sys::{Array, Function, Math, Object, Reflect, Uint8Array, global};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, Once, OnceLock};
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::{Closure, JsValue};
+use wasm_bindgen_futures::spawn_local;
 
-// There is no shutdown mechanism on the web.
-static _SHUTDOWN_EVENTS: &ShutdownEventsLock = &SHUTDOWN_EVENTS;
+static PAGE_WATCHER: Once = Once::new();
+static WORKER_LISTENER: Once = Once::new();
 
 pub fn start_rust_logic_real<F, T>(main_fn: F) -> Result<(), AppError>
 where
   F: Fn() -> T + 'static,
 {
-  // Add kind description for panics.
-  #[cfg(debug_assertions)]
-  {
-    use crate::debug_print;
-    use std::panic::set_hook;
-    set_hook(Box::new(|panic_info| {
-      debug_print!("A panic occurred in Rust.\n{}", panic_info);
-    }));
-  }
+  prepare_start();
 
   // Run the main function.
   main_fn();
@@ -26,31 +31,416 @@ where
   Ok(())
 }
 
-#[wasm_bindgen]
-extern "C" {
-  // The reason this extern function is marked `catch`
-  // and returns a `Result` is that the
-  // `rinfBindings` JavaScript object created by Dart
-  // does not exist in web workers; it is only available
-  // in the main JavaScript thread. Loading the function
-  // fails in web workers.
-  #[wasm_bindgen(js_namespace = rinfBindings, catch)]
-  pub fn rinf_send_rust_signal_extern(
-    endpoint: &str,
-    message_bytes: Uint8Array,
-    binary: Uint8Array,
-  ) -> Result<(), JsValue>;
+/// The main function handed over to a web worker.
+type WorkerMain = Box<dyn FnOnce() + Send>;
+
+pub fn start_rust_logic_in_worker_real<F, T>(main_fn: F) -> Result<(), AppError>
+where
+  F: Fn() -> T + Send + 'static,
+{
+  prepare_start();
+
+  // Run the main function in a web worker.
+  let worker_main: WorkerMain = Box::new(move || {
+    main_fn();
+  });
+  spawn_worker(worker_main)
+}
+
+/// Main functions waiting for their web workers, keyed by worker IDs.
+/// Each one is taken out either by its worker once it starts,
+/// or by the main thread if the worker fails to start or is terminated,
+/// so it's never run or dropped twice.
+static WORKER_MAINS: Mutex<BTreeMap<usize, WorkerMain>> =
+  Mutex::new(BTreeMap::new());
+
+static NEXT_WORKER_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+  /// The web worker running the Rust logic and its ID,
+  /// kept on the main thread to terminate it later.
+  static RUST_WORKER: RefCell<Option<(JsValue, usize)>> =
+    const { RefCell::new(None) };
+}
+
+pub fn run_worker_main_real(worker_id: usize) {
+  let worker_main = WORKER_MAINS.lock().recover().remove(&worker_id);
+  if let Some(worker_main) = worker_main {
+    worker_main();
+  }
+}
+
+/// Prepares the main thread to run the Rust logic.
+fn prepare_start() {
+  // Report panics in the CLI and to Dart.
+  install_panic_hook();
+
+  // Notify Rust when the page is closed.
+  PAGE_WATCHER.call_once(watch_page);
+
+  // Pass Rust signals from web workers to Dart.
+  WORKER_LISTENER.call_once(listen_to_workers);
+
+  // Notify that Dart has stopped
+  // to finish the previous Rust logic after Dart's hot restart,
+  // and clear shutdown events to prepare for a fresh start.
+  SHUTDOWN_EVENTS.stop_dart();
+  terminate_worker_after_hooks();
+  SHUTDOWN_EVENTS.reset();
+}
+
+/// Starts a module web worker that loads the same JavaScript module
+/// with the shared WebAssembly memory, and runs the main function there.
+/// The worker is terminated when the page is unloaded
+/// or the Rust logic starts again after Dart's hot restart,
+/// once the shutdown hooks of its session have finished.
+fn spawn_worker(worker_main: WorkerMain) -> Result<(), AppError> {
+  let module_url = get_rinf_bindings()
+    .and_then(|bindings| Reflect::get(&bindings, &"jsModuleUrl".into()).ok())
+    .and_then(|value| value.as_string())
+    .ok_or(AppError::CannotStartWorker)?;
+  let script = format!(
+    r#"import init, * as wasmBindings from "{module_url}";
+self.onmessage = async (event) => {{
+  self.onmessage = null;
+  const [module, memory, workerId] = event.data;
+  try {{
+    await init({{ module_or_path: module, memory }});
+  }} catch (error) {{
+    self.postMessage(workerId);
+    throw error;
+  }}
+  wasmBindings.rinf_run_worker_main_extern(workerId);
+}};"#
+  );
+  let worker = create_worker(&script).ok_or(AppError::CannotStartWorker)?;
+  let post_message = Reflect::get(&worker, &"postMessage".into())
+    .ok()
+    .and_then(|value| value.dyn_into::<Function>().ok())
+    .ok_or(AppError::CannotStartWorker)?;
+  let worker_id = NEXT_WORKER_ID.fetch_add(1, Ordering::Relaxed);
+  WORKER_MAINS.lock().recover().insert(worker_id, worker_main);
+
+  // The worker reports a failed initialization with a message,
+  // and a failed script load with an error event.
+  // Either way, the main function that won't run is dropped here.
+  let on_failure = Closure::<dyn Fn()>::new(move || {
+    WORKER_MAINS.lock().recover().remove(&worker_id);
+  });
+  let _ = Reflect::set(&worker, &"onmessage".into(), on_failure.as_ref());
+  let _ = Reflect::set(&worker, &"onerror".into(), on_failure.as_ref());
+  on_failure.forget();
+
+  let message = Array::of3(
+    &wasm_bindgen::module(),
+    &wasm_bindgen::memory(),
+    &JsValue::from_f64(worker_id as f64),
+  );
+  if post_message.call1(&worker, &message).is_err() {
+    terminate_worker(&worker, worker_id);
+    return Err(AppError::CannotStartWorker);
+  }
+  RUST_WORKER.set(Some((worker, worker_id)));
+  Ok(())
+}
+
+/// Terminates the web worker running the previous Rust logic, if any,
+/// after the shutdown hooks of its session have finished.
+/// This should be called after Dart stops and before the events are reset.
+fn terminate_worker_after_hooks() {
+  let Some((worker, worker_id)) = RUST_WORKER.take() else {
+    return;
+  };
+  let hooks_finished = hooks_finished();
+  spawn_local(async move {
+    hooks_finished.await;
+    terminate_worker(&worker, worker_id);
+  });
+}
+
+/// Terminates a web worker right away,
+/// dropping its main function if it hasn't started yet.
+fn terminate_worker(worker: &JsValue, worker_id: usize) {
+  let terminate = Reflect::get(worker, &"terminate".into())
+    .ok()
+    .and_then(|value| value.dyn_into::<Function>().ok());
+  if let Some(terminate) = terminate {
+    let _ = terminate.call0(worker);
+  }
+  WORKER_MAINS.lock().recover().remove(&worker_id);
+}
+
+/// Creates a module web worker from the source code of a script.
+fn create_worker(script: &str) -> Option<JsValue> {
+  let blob_options = Object::new();
+  Reflect::set(&blob_options, &"type".into(), &"text/javascript".into())
+    .ok()?;
+  let blob = construct_global(
+    "Blob",
+    &Array::of2(&Array::of1(&script.into()), &blob_options),
+  )?;
+  let url_class = Reflect::get(&global(), &"URL".into()).ok()?;
+  let create_object_url = Reflect::get(&url_class, &"createObjectURL".into())
+    .ok()?
+    .dyn_into::<Function>()
+    .ok()?;
+  let script_url = create_object_url.call1(&url_class, &blob).ok()?;
+  let worker_options = Object::new();
+  Reflect::set(&worker_options, &"type".into(), &"module".into()).ok()?;
+  construct_global("Worker", &Array::of2(&script_url, &worker_options))
+}
+
+/// Creates an object with a constructor of the global JavaScript scope.
+fn construct_global(name: &str, arguments: &Array) -> Option<JsValue> {
+  let constructor = Reflect::get(&global(), &name.into())
+    .ok()?
+    .dyn_into::<Function>()
+    .ok()?;
+  Reflect::construct(&constructor, arguments).ok()
 }
 
+/// Listens for browser events that tell the page is going away.
+/// Unloading the page with `pagehide` stops Dart,
+/// which runs synchronous shutdown hooks right away.
+/// A page that enters the back/forward cache can be restored later,
+/// so it doesn't stop Dart, and `beforeunload` is not used
+/// because the user can still cancel leaving the page.
+/// Hiding the page with `visibilitychange` doesn't stop Dart,
+/// because it also happens when switching tabs,
+/// but batched Rust signals are flushed
+/// in case the browser freezes or discards the hidden page.
+fn watch_page() {
+  let on_page_hide = Closure::<dyn Fn(JsValue)>::new(|event: JsValue| {
+    if is_persisted(&event) {
+      let _ = flush();
+    } else {
+      SHUTDOWN_EVENTS.stop_dart();
+      terminate_worker_after_hooks();
+    }
+  });
+  add_event_listener("pagehide", on_page_hide.as_ref());
+  on_page_hide.forget();
+
+  let on_visibility_change = Closure::<dyn Fn()>::new(|| {
+    if is_page_hidden() {
+      let _ = flush();
+    }
+  });
+  add_event_listener("visibilitychange", on_visibility_change.as_ref());
+  on_visibility_change.forget();
+}
+
+/// Checks whether a `pagehide` event puts the page
+/// into the back/forward cache instead of unloading it.
+fn is_persisted(event: &JsValue) -> bool {
+  Reflect::get(event, &JsValue::from_str("persisted"))
+    .ok()
+    .and_then(|persisted| persisted.as_bool())
+    .unwrap_or(false)
+}
+
+fn add_event_listener(event_name: &str, listener: &JsValue) {
+  let target = global();
+  let add_event_listener =
+    Reflect::get(&target, &JsValue::from_str("addEventListener"))
+      .ok()
+      .and_then(|value| value.dyn_into::<Function>().ok());
+  if let Some(add_event_listener) = add_event_listener {
+    let _ = add_event_listener.call2(
+      &target,
+      &JsValue::from_str(event_name),
+      listener,
+    );
+  }
+}
+
+fn is_page_hidden() -> bool {
+  Reflect::get(&global(), &JsValue::from_str("document"))
+    .and_then(|document| {
+      Reflect::get(&document, &JsValue::from_str("visibilityState"))
+    })
+    .ok()
+    .and_then(|state| state.as_string())
+    .is_some_and(|state| state == "hidden")
+}
+
+/// Finds the global bindings object created by Dart.
+/// Each namespace has its own object,
+/// such as `rinfBindings` or `rinfBindings_sdk`.
+fn get_rinf_bindings() -> Option<JsValue> {
+  let namespace = get_namespace();
+  let bindings_name = if namespace.is_empty() {
+    "rinfBindings".to_owned()
+  } else {
+    format!("rinfBindings_{namespace}")
+  };
+  let bindings = Reflect::get(&global(), &bindings_name.into()).ok()?;
+  if bindings.is_undefined() {
+    return None;
+  }
+  Some(bindings)
+}
+
+/// Finds the JavaScript function created by Dart
+/// that receives Rust signals.
+/// Each namespace has its own global bindings object,
+/// such as `rinfBindings` or `rinfBindings_sdk`.
+/// Loading the function fails in web workers,
+/// because the bindings object created by Dart
+/// is only available in the main JavaScript thread.
+/// Web workers forward signals to the main thread instead.
+fn get_send_rust_signal_extern() -> Option<Function> {
+  let bindings = get_rinf_bindings()?;
+  let function_name = JsValue::from_str("rinf_send_rust_signal_extern");
+  let function = Reflect::get(&bindings, &function_name).ok()?;
+  function.dyn_into().ok()
+}
+
+/// Sends a Rust signal to Dart.
+/// The message bytes are lent to Dart as a view over the WebAssembly memory
+/// without being copied, because Dart decodes them during the call.
+/// The binary is copied once into a new JavaScript array
+/// that Dart owns from then on, because it outlives the call.
+/// Rust frees both vectors after the call returns.
 pub fn send_rust_signal_real(
-  endpoint: &str,
+  endpoint_id: u32,
   message_bytes: Vec<u8>,
   binary: Vec<u8>,
 ) -> Result<(), AppError> {
-  let result = rinf_send_rust_signal_extern(
-    endpoint,
-    Uint8Array::from(message_bytes.as_slice()),
-    Uint8Array::from(binary.as_slice()),
+  let Some(send_rust_signal_extern) = get_send_rust_signal_extern() else {
+    let signal = QueuedSignal {
+      endpoint_id,
+      message_bytes,
+      binary,
+    };
+    return post_to_main_thread(vec![signal]);
+  };
+  // The view is valid while the vector is alive and nothing allocates,
+  // which holds until the synchronous call returns.
+  let message_view = unsafe { Uint8Array::view(&message_bytes) };
+  let result = send_rust_signal_extern.call3(
+    &JsValue::NULL,
+    &endpoint_id.into(),
+    &message_view,
+    &Uint8Array::from(binary.as_slice()),
   );
-  result.map_err(|_| AppError::NoBindings)
+  drop(message_bytes);
+  result.map(|_| ()).map_err(|_| AppError::NoBindings)
+}
+
+/// Sends multiple Rust signals to Dart with a single call.
+/// Each item of the array contains the endpoint ID,
+/// message bytes, and binary of a signal, which Dart unpacks in order.
+pub fn send_rust_signal_batch_real(
+  signals: Vec<QueuedSignal>,
+) -> Result<(), AppError> {
+  let Some(send_rust_signal_extern) = get_send_rust_signal_extern() else {
+    return post_to_main_thread(signals);
+  };
+  // Views are created only after all allocations for the array,
+  // as growing the WebAssembly memory can invalidate them.
+  let packed = Array::new_with_length(signals.len() as u32);
+  let binaries: Vec<Uint8Array> = signals
+    .iter()
+    .map(|signal| Uint8Array::from(signal.binary.as_slice()))
+    .collect();
+  for (index, (signal, binary)) in signals.iter().zip(&binaries).enumerate() {
+    let message_view = unsafe { Uint8Array::view(&signal.message_bytes) };
+    let item = Array::of3(&signal.endpoint_id.into(), &message_view, binary);
+    packed.set(index as u32, item.into());
+  }
+  let result = send_rust_signal_extern.call3(
+    &JsValue::NULL,
+    &BATCH_ENDPOINT_ID.into(),
+    &packed,
+    &Uint8Array::new_with_length(0),
+  );
+  drop(signals);
+  result.map(|_| ()).map_err(|_| AppError::NoBindings)
+}
+
+/// Rust signals from web workers waiting to be sent by the main thread.
+/// Web workers share the WebAssembly memory with the main thread,
+/// so signals are handed over here without copying their binaries,
+/// and they stay owned by Rust the whole time.
+/// This queue is lock-free, because the main thread cannot block.
+static WORKER_SIGNALS: SegQueue<QueuedSignal> = SegQueue::new();
+
+/// Name of the `BroadcastChannel` that tells the main thread
+/// about Rust signals from web workers.
+/// Web workers share the memory of the WebAssembly module,
+/// so they read the same name.
+/// It includes a random ID, because other pages of the same origin
+/// can also open channels with the same namespace.
+static WORKER_CHANNEL_NAME: OnceLock<String> = OnceLock::new();
+
+thread_local! {
+  /// The `BroadcastChannel` of this JavaScript thread, if available.
+  static WORKER_CHANNEL: Option<JsValue> = open_worker_channel();
+}
+
+fn open_worker_channel() -> Option<JsValue> {
+  let name = WORKER_CHANNEL_NAME.get()?;
+  let constructor = Reflect::get(&global(), &"BroadcastChannel".into())
+    .ok()?
+    .dyn_into::<Function>()
+    .ok()?;
+  Reflect::construct(&constructor, &Array::of1(&name.into())).ok()
+}
+
+/// Checks whether the current JavaScript thread is a web worker.
+fn is_web_worker() -> bool {
+  Reflect::has(&global(), &"WorkerGlobalScope".into()).unwrap_or(false)
+}
+
+/// Makes the main thread pass Rust signals from web workers to Dart.
+fn listen_to_workers() {
+  let name = WORKER_CHANNEL_NAME.get_or_init(|| {
+    let random_id = (Math::random() * 1e16) as u64;
+    format!("rinf_{}_{random_id}", get_namespace())
+  });
+  // Messages on the channel carry no data.
+  // Each one only means that there might be signals in the queue,
+  // so extra or merged messages are harmless.
+  let on_message = Closure::<dyn Fn()>::new(|| {
+    let signals: Vec<QueuedSignal> =
+      std::iter::from_fn(|| WORKER_SIGNALS.pop()).collect();
+    if !signals.is_empty() {
+      let _ = send_rust_signal_batch_real(signals);
+    }
+  });
+  WORKER_CHANNEL.with(|channel| {
+    if let Some(channel) = channel {
+      let _ = Reflect::set(channel, &"onmessage".into(), on_message.as_ref());
+    } else {
+      debug_print!("Could not open the channel `{name}` for web workers");
+    }
+  });
+  on_message.forget();
+}
+
+/// Hands Rust signals over to the main thread from a web worker.
+/// The signals are put in the shared queue,
+/// and the main thread is notified to send them to Dart.
+/// This way, binaries are not copied between threads.
+fn post_to_main_thread(signals: Vec<QueuedSignal>) -> Result<(), AppError> {
+  if !is_web_worker() {
+    return Err(AppError::NoBindings);
+  }
+  WORKER_CHANNEL.with(|channel| {
+    let channel = channel.as_ref().ok_or(AppError::NoBindings)?;
+    let post_message = Reflect::get(channel, &"postMessage".into())
+      .ok()
+      .and_then(|value| value.dyn_into::<Function>().ok())
+      .ok_or(AppError::NoBindings)?;
+    for signal in signals {
+      WORKER_SIGNALS.push(signal);
+    }
+    // If notifying fails, the signals are still sent
+    // along with the next notification from any web worker.
+    post_message
+      .call1(channel, &JsValue::NULL)
+      .map(|_| ())
+      .map_err(|_| AppError::NoBindings)
+  })
 }
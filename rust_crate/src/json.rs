@@ -0,0 +1,18 @@
+use crate::AppError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Converts a signal into JSON text
+/// with serde's default JSON representation.
+/// The output matches `toJson` of the generated Dart classes
+/// when `gen_json` is enabled in the Rinf configuration,
+/// which makes it useful for debugging dumps.
+pub fn to_json<T: Serialize>(signal: &T) -> Result<String, AppError> {
+  serde_json::to_string(signal).map_err(|_| AppError::CannotEncodeMessage)
+}
+
+/// Creates a signal from JSON text
+/// with serde's default JSON representation.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, AppError> {
+  serde_json::from_str(json).map_err(|_| AppError::CannotDecodeMessage)
+}
@@ -1,8 +1,25 @@
+mod batch;
 mod channel;
+mod endpoint;
 mod error;
+#[cfg(not(target_family = "wasm"))]
+mod executor;
+#[cfg(all(feature = "headless", not(target_family = "wasm")))]
+mod headless;
+#[cfg(all(feature = "inspector", not(target_family = "wasm")))]
+mod inspector;
+#[cfg(feature = "json")]
+mod json;
+mod lifecycle;
+mod logging;
 mod macros;
+mod panic;
+#[cfg(all(feature = "recording", not(target_family = "wasm")))]
+mod recording;
 mod shutdown;
 mod signal_trait;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod traits;
 
 mod interface;
@@ -11,10 +28,52 @@ mod interface_os;
 #[cfg(target_family = "wasm")]
 mod interface_web;
 
+pub use batch::{batch, flush, set_batch_window};
 pub use channel::{SignalReceiver, SignalSender, signal_channel};
+pub use endpoint::{
+  DartSignalEndpoint, DartSignalId, OUT_ENDPOINT_ID, RustSignalId,
+  send_dart_signal,
+};
 pub use error::AppError;
-pub use interface::{DartSignalPack, send_rust_signal, start_rust_logic};
-pub use shutdown::dart_shutdown;
+#[cfg(all(feature = "headless", not(target_family = "wasm")))]
+pub use headless::{HeadlessAddress, run_headless};
+#[cfg(not(target_family = "wasm"))]
+pub use interface::{DartPostCObjectFnType, prepare_isolate, stop_rust_logic};
+pub use interface::{
+  DartSignalPack, get_namespace, send_rust_signal, set_namespace,
+  start_rust_logic,
+};
+#[cfg(target_family = "wasm")]
+pub use interface::{run_worker_main, start_rust_logic_in_worker};
+#[cfg(feature = "json")]
+pub use json::{from_json, to_json};
+pub use lifecycle::{
+  AppLifecycleState, LifecycleEvent, app_lifecycle_state, lifecycle,
+};
+#[cfg(feature = "tracing")]
+pub use logging::RinfLayer;
+pub use logging::{
+  LogLevel, LogRecord, get_log_level, is_log_enabled, send_log_record,
+  set_log_level,
+};
+#[cfg(feature = "log")]
+pub use logging::{RinfLogger, init_logger};
+pub use panic::{
+  PanicReport, PanicReporting, catch_panic, set_panic_reporting,
+};
+#[cfg(all(feature = "recording", not(target_family = "wasm")))]
+pub use recording::{
+  RecordedDirection, RecordedSignal, Recording, ReplaySpeed, start_recording,
+  stop_recording,
+};
+#[cfg(target_family = "wasm")]
+pub use shutdown::on_shutdown_local;
+#[cfg(not(target_family = "wasm"))]
+pub use shutdown::set_shutdown_deadline;
+pub use shutdown::{
+  ShutdownToken, TokenFuture, dart_shutdown, on_shutdown, on_shutdown_sync,
+  shutdown_token,
+};
 pub use signal_trait::{
   DartSignal, DartSignalBinary, RustSignal, RustSignalBinary, SignalPiece,
 };
@@ -25,3 +84,5 @@ pub use rinf_proc::{
 
 #[doc(hidden)]
 pub use bincode::{deserialize, serialize};
+#[doc(hidden)]
+pub use inventory;
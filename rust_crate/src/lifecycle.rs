@@ -0,0 +1,108 @@
+use crate::endpoint::LIFECYCLE_ENDPOINT_ID;
+use crate::traits::GuardRecovery;
+use crate::{
+  DartSignalEndpoint, SignalReceiver, SignalSender, debug_print, deserialize,
+  signal_channel,
+};
+use std::sync::Mutex;
+
+/// The state of the Flutter app,
+/// which mirrors `AppLifecycleState` in Flutter.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AppLifecycleState {
+  /// The app is visible and responding to user input.
+  Resumed,
+  /// The app is visible but not focused.
+  Inactive,
+  /// The app is not visible.
+  Hidden,
+  /// The app is not visible and not running its UI,
+  /// which usually means that it's in the background on mobile.
+  Paused,
+  /// The app is about to be destroyed.
+  Detached,
+}
+
+/// An event in the lifecycle of the Flutter app.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LifecycleEvent {
+  /// The state of the app has changed.
+  StateChanged(AppLifecycleState),
+  /// The operating system asked the app to release memory,
+  /// such as by dropping caches.
+  MemoryPressure,
+}
+
+impl LifecycleEvent {
+  /// Converts the code sent from Dart into an event.
+  fn from_code(code: u32) -> Option<Self> {
+    let event = match code {
+      0 => Self::StateChanged(AppLifecycleState::Resumed),
+      1 => Self::StateChanged(AppLifecycleState::Inactive),
+      2 => Self::StateChanged(AppLifecycleState::Hidden),
+      3 => Self::StateChanged(AppLifecycleState::Paused),
+      4 => Self::StateChanged(AppLifecycleState::Detached),
+      5 => Self::MemoryPressure,
+      _ => return None,
+    };
+    Some(event)
+  }
+}
+
+struct LifecycleState {
+  current: Option<AppLifecycleState>,
+  senders: Vec<SignalSender<LifecycleEvent>>,
+}
+
+static LIFECYCLE_STATE: Mutex<LifecycleState> = Mutex::new(LifecycleState {
+  current: None,
+  senders: Vec::new(),
+});
+
+/// Returns a receiver of lifecycle events from the Flutter app,
+/// such as going to the background or memory pressure.
+/// Unlike Dart signal receivers, every receiver from this function
+/// gets all events sent after it was created.
+pub fn lifecycle() -> SignalReceiver<LifecycleEvent> {
+  let (sender, receiver) = signal_channel();
+  let mut guard = LIFECYCLE_STATE.lock().recover();
+  guard.senders.push(sender);
+  receiver
+}
+
+/// Returns the latest state of the Flutter app,
+/// or `None` if Dart has not reported it yet.
+pub fn app_lifecycle_state() -> Option<AppLifecycleState> {
+  LIFECYCLE_STATE.lock().recover().current
+}
+
+/// Sends a lifecycle event to all receivers.
+pub fn broadcast_lifecycle_event(event: LifecycleEvent) {
+  let mut guard = LIFECYCLE_STATE.lock().recover();
+  if let LifecycleEvent::StateChanged(state) = event {
+    guard.current = Some(state);
+  }
+  // Senders of dropped receivers are removed.
+  guard.senders.retain(|sender| !sender.is_closed());
+  for sender in &guard.senders {
+    sender.send(event);
+  }
+}
+
+fn receive_lifecycle_event(message_bytes: &[u8], _binary: Vec<u8>) {
+  let event = deserialize::<u32>(message_bytes)
+    .ok()
+    .and_then(LifecycleEvent::from_code);
+  match event {
+    Some(inner) => broadcast_lifecycle_event(inner),
+    None => debug_print!("Received an unknown lifecycle event from Dart"),
+  }
+}
+
+inventory::submit! {
+  DartSignalEndpoint {
+    id: LIFECYCLE_ENDPOINT_ID,
+    name: "RinfLifecycle",
+    handler: receive_lifecycle_event,
+  }
+}
@@ -0,0 +1,257 @@
+use crate::endpoint::LOG_ENDPOINT_ID;
+use crate::send_rust_signal;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The most verbose level that is forwarded to Dart.
+static MAX_LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// Severity of a log record.
+/// Lower values are more severe.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[repr(u8)]
+pub enum LogLevel {
+  Error = 1,
+  Warn = 2,
+  Info = 3,
+  Debug = 4,
+  Trace = 5,
+}
+
+impl Display for LogLevel {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let name = match self {
+      Self::Error => "ERROR",
+      Self::Warn => "WARN",
+      Self::Info => "INFO",
+      Self::Debug => "DEBUG",
+      Self::Trace => "TRACE",
+    };
+    write!(f, "{name}")
+  }
+}
+
+/// A structured log record that is forwarded to Dart.
+/// Unlike `debug_print!`, log records are also sent in release mode,
+/// so that Flutter can collect production logs from Rust.
+pub struct LogRecord {
+  pub level: LogLevel,
+  /// The module or component that produced this record.
+  pub target: String,
+  pub message: String,
+  /// The Rust source file that produced this record, if known.
+  pub file: Option<String>,
+  /// The line in the Rust source file, if known.
+  pub line: Option<u32>,
+  /// Extra key-value pairs attached to this record.
+  pub fields: Vec<(String, String)>,
+}
+
+/// Sets the most verbose level of log records that are sent to Dart.
+/// Records that are more verbose than this level are dropped in Rust
+/// without being sent. The default level is `LogLevel::Info`.
+pub fn set_log_level(level: LogLevel) {
+  MAX_LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+  #[cfg(feature = "log")]
+  log::set_max_level(log_bridge::to_level_filter(level));
+}
+
+/// Returns the most verbose level of log records that are sent to Dart.
+pub fn get_log_level() -> LogLevel {
+  match MAX_LOG_LEVEL.load(Ordering::Relaxed) {
+    1 => LogLevel::Error,
+    2 => LogLevel::Warn,
+    3 => LogLevel::Info,
+    4 => LogLevel::Debug,
+    _ => LogLevel::Trace,
+  }
+}
+
+/// Checks whether a record with the given level would be sent to Dart.
+pub fn is_log_enabled(level: LogLevel) -> bool {
+  level <= get_log_level()
+}
+
+/// Sends a log record to Dart if its level is enabled.
+/// Failures are ignored because there is no place to report them,
+/// such as when Dart is not ready yet.
+pub fn send_log_record(record: LogRecord) {
+  if !is_log_enabled(record.level) {
+    return;
+  }
+  // Tuples are serialized without derive macros,
+  // and the Dart side reads the same layout.
+  let encoded = bincode::serialize(&(
+    record.level as u8,
+    record.target,
+    record.message,
+    record.file,
+    record.line,
+    record.fields,
+  ));
+  if let Ok(message_bytes) = encoded {
+    let _ = send_rust_signal(LOG_ENDPOINT_ID, message_bytes, Vec::new());
+  }
+}
+
+#[cfg(feature = "log")]
+mod log_bridge {
+  use super::{LogLevel, LogRecord, is_log_enabled, send_log_record};
+  use log::kv::{Error, Key, Value, VisitSource};
+  use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+  /// A logger for the `log` crate that forwards records to Dart.
+  pub struct RinfLogger;
+
+  impl Log for RinfLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+      is_log_enabled(from_level(metadata.level()))
+    }
+
+    fn log(&self, record: &Record) {
+      if !self.enabled(record.metadata()) {
+        return;
+      }
+      let mut fields = Vec::new();
+      let _ = record.key_values().visit(&mut FieldVisitor(&mut fields));
+      send_log_record(LogRecord {
+        level: from_level(record.level()),
+        target: record.target().to_owned(),
+        message: record.args().to_string(),
+        file: record.file().map(|file| file.to_owned()),
+        line: record.line(),
+        fields,
+      });
+    }
+
+    fn flush(&self) {}
+  }
+
+  struct FieldVisitor<'a>(&'a mut Vec<(String, String)>);
+
+  impl<'kvs> VisitSource<'kvs> for FieldVisitor<'_> {
+    fn visit_pair(
+      &mut self,
+      key: Key<'kvs>,
+      value: Value<'kvs>,
+    ) -> Result<(), Error> {
+      self.0.push((key.to_string(), value.to_string()));
+      Ok(())
+    }
+  }
+
+  /// Installs `RinfLogger` as the global logger of the `log` crate.
+  /// This fails if another logger was already installed.
+  pub fn init_logger(level: LogLevel) -> Result<(), SetLoggerError> {
+    log::set_logger(&RinfLogger)?;
+    super::set_log_level(level);
+    Ok(())
+  }
+
+  fn from_level(level: log::Level) -> LogLevel {
+    match level {
+      log::Level::Error => LogLevel::Error,
+      log::Level::Warn => LogLevel::Warn,
+      log::Level::Info => LogLevel::Info,
+      log::Level::Debug => LogLevel::Debug,
+      log::Level::Trace => LogLevel::Trace,
+    }
+  }
+
+  pub fn to_level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+      LogLevel::Error => LevelFilter::Error,
+      LogLevel::Warn => LevelFilter::Warn,
+      LogLevel::Info => LevelFilter::Info,
+      LogLevel::Debug => LevelFilter::Debug,
+      LogLevel::Trace => LevelFilter::Trace,
+    }
+  }
+}
+
+#[cfg(feature = "tracing")]
+mod tracing_bridge {
+  use super::{LogLevel, LogRecord, is_log_enabled, send_log_record};
+  use std::fmt::Debug;
+  use tracing_core::field::{Field, Visit};
+  use tracing_core::{Event, Level, Subscriber};
+  use tracing_subscriber::layer::{Context, Layer};
+
+  /// A layer for `tracing-subscriber` that forwards events to Dart.
+  /// Fields other than `message` are attached as key-value pairs.
+  #[derive(Default)]
+  pub struct RinfLayer;
+
+  impl RinfLayer {
+    pub fn new() -> Self {
+      Self
+    }
+  }
+
+  impl<S: Subscriber> Layer<S> for RinfLayer {
+    // The level is checked here instead of in `enabled`,
+    // which would disable events for the other layers as well.
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+      let metadata = event.metadata();
+      let level = from_level(metadata.level());
+      if !is_log_enabled(level) {
+        return;
+      }
+      let mut visitor = FieldVisitor {
+        message: String::new(),
+        fields: Vec::new(),
+      };
+      event.record(&mut visitor);
+      send_log_record(LogRecord {
+        level,
+        target: metadata.target().to_owned(),
+        message: visitor.message,
+        file: metadata.file().map(|file| file.to_owned()),
+        line: metadata.line(),
+        fields: visitor.fields,
+      });
+    }
+  }
+
+  struct FieldVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+  }
+
+  impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+      if field.name() == "message" {
+        self.message = value.to_owned();
+      } else {
+        self
+          .fields
+          .push((field.name().to_owned(), value.to_owned()));
+      }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+      if field.name() == "message" {
+        self.message = format!("{value:?}");
+      } else {
+        self
+          .fields
+          .push((field.name().to_owned(), format!("{value:?}")));
+      }
+    }
+  }
+
+  fn from_level(level: &Level) -> LogLevel {
+    match *level {
+      Level::ERROR => LogLevel::Error,
+      Level::WARN => LogLevel::Warn,
+      Level::INFO => LogLevel::Info,
+      Level::DEBUG => LogLevel::Debug,
+      _ => LogLevel::Trace,
+    }
+  }
+}
+
+#[cfg(feature = "log")]
+pub use log_bridge::{RinfLogger, init_logger};
+#[cfg(feature = "tracing")]
+pub use tracing_bridge::RinfLayer;
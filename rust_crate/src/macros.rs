@@ -3,27 +3,119 @@
 /// needed to communicate with Dart.
 /// This should be used once, and only once,
 /// at the root of the `hub` crate.
+///
+/// To load multiple Rust libraries in one Flutter app,
+/// each library can use its own namespace
+/// with `write_interface!(namespace = "sdk")`.
+///
+/// On the web, `write_interface!(web_worker = true)` runs the Rust logic
+/// in a dedicated web worker instead of the main JavaScript thread.
+/// Both options can be given together, with `namespace` first.
 macro_rules! write_interface {
   () => {
+    $crate::write_interface!(@symbols "", "rinf_", false);
+  };
+  (namespace = $namespace:literal) => {
+    $crate::write_interface!(
+      @symbols $namespace, concat!("rinf_", $namespace, "_"), false
+    );
+  };
+  (web_worker = $web_worker:literal) => {
+    $crate::write_interface!(@symbols "", "rinf_", $web_worker);
+  };
+  (namespace = $namespace:literal, web_worker = $web_worker:literal) => {
+    $crate::write_interface!(
+      @symbols $namespace, concat!("rinf_", $namespace, "_"), $web_worker
+    );
+  };
+  (@symbols $namespace:literal, $prefix:expr, $web_worker:literal) => {
     #[cfg(not(target_family = "wasm"))]
-    #[unsafe(no_mangle)]
+    #[unsafe(export_name = concat!($prefix, "start_rust_logic_extern"))]
     extern "C" fn rinf_start_rust_logic_extern() {
       use rinf::debug_print;
+      $crate::set_namespace($namespace);
       let result = $crate::start_rust_logic(main);
       if let Err(err) = result {
         debug_print!("{}", err);
       }
     }
 
+    #[cfg(not(target_family = "wasm"))]
+    #[unsafe(export_name = concat!($prefix, "stop_rust_logic_extern"))]
+    extern "C" fn rinf_stop_rust_logic_extern() {
+      $crate::stop_rust_logic();
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[unsafe(export_name = concat!($prefix, "prepare_isolate_extern"))]
+    extern "C" fn rinf_prepare_isolate_extern(
+      store_post_object: $crate::DartPostCObjectFnType,
+      port: i64,
+    ) {
+      $crate::prepare_isolate(store_post_object, port);
+    }
+
+    // All Dart signals are dispatched by this single function
+    // with endpoint IDs assigned by `rinf gen`.
+    #[cfg(not(target_family = "wasm"))]
+    #[unsafe(export_name = concat!($prefix, "send_dart_signal"))]
+    unsafe extern "C" fn rinf_send_dart_signal_extern(
+      endpoint_id: u32,
+      message_pointer: *const u8,
+      message_size: usize,
+      binary_pointer: *const u8,
+      binary_size: usize,
+    ) {
+      use std::slice::from_raw_parts;
+      // Pointers of empty buffers from Dart can be null.
+      let message_bytes: &[u8] = match message_size {
+        0 => &[],
+        _ => unsafe { from_raw_parts(message_pointer, message_size) },
+      };
+      // The binary is owned by Dart, so it's copied only once here.
+      let binary: Vec<u8> = match binary_size {
+        0 => Vec::new(),
+        _ => unsafe { from_raw_parts(binary_pointer, binary_size) }.to_vec(),
+      };
+      $crate::send_dart_signal(endpoint_id, message_bytes, binary);
+    }
+
+    // Each web library is a separate JavaScript module,
+    // so exported names don't collide.
     #[cfg(target_family = "wasm")]
     #[wasm_bindgen::prelude::wasm_bindgen]
     pub fn rinf_start_rust_logic_extern() {
       use rinf::debug_print;
-      let result = $crate::start_rust_logic(main);
+      $crate::set_namespace($namespace);
+      let result = if $web_worker {
+        $crate::start_rust_logic_in_worker(main)
+      } else {
+        $crate::start_rust_logic(main)
+      };
       if let Err(err) = result {
         debug_print!("{}", err);
       }
     }
+
+    // A web worker started by Rust calls this
+    // after loading the same JavaScript module.
+    #[cfg(target_family = "wasm")]
+    #[wasm_bindgen::prelude::wasm_bindgen]
+    pub fn rinf_run_worker_main_extern(worker_id: usize) {
+      $crate::run_worker_main(worker_id);
+    }
+
+    // The binary is copied from JavaScript into an owned vector
+    // only once, which is then moved to the receiver.
+    #[cfg(target_family = "wasm")]
+    #[wasm_bindgen::prelude::wasm_bindgen]
+    pub fn rinf_send_dart_signal(
+      endpoint_id: u32,
+      message_bytes: &[u8],
+      binary: Vec<u8>,
+    ) {
+      $crate::send_dart_signal(endpoint_id, message_bytes, binary);
+    }
   };
 }
 
@@ -41,7 +133,7 @@ macro_rules! debug_print {
       #[cfg(debug_assertions)]
       {
         let result = $crate::send_rust_signal(
-          "RinfOut", // Special message ID for Rust output
+          $crate::OUT_ENDPOINT_ID,
           Vec::new(),
           rust_report.clone().into_bytes(),
         );
@@ -0,0 +1,176 @@
+use crate::AppError;
+use crate::batch::send_immediately;
+use crate::endpoint::PANIC_ENDPOINT_ID;
+use crate::traits::GuardRecovery;
+use std::fmt::{Display, Formatter};
+use std::panic::{
+  AssertUnwindSafe, PanicHookInfo, catch_unwind, set_hook, take_hook,
+};
+use std::pin::Pin;
+use std::sync::{Mutex, Once};
+use std::task::{Context, Poll};
+use std::thread::current;
+
+static PANIC_REPORTING: Mutex<PanicReporting> = Mutex::new(PanicReporting {
+  send_to_dart: false,
+  capture_backtrace: false,
+});
+
+/// Options for reporting panics in Rust.
+/// In debug mode, panics are always printed in the CLI.
+#[derive(Clone, Copy, Default)]
+pub struct PanicReporting {
+  /// Whether to send panic reports to Dart in any build mode,
+  /// which can be received from `rustPanicStream` in Dart.
+  pub send_to_dart: bool,
+  /// Whether to capture the backtrace of each panic.
+  /// This is always done with the `show-backtrace` feature.
+  pub capture_backtrace: bool,
+}
+
+/// Configures how panics are reported.
+/// Reporting to Dart is disabled by default.
+pub fn set_panic_reporting(options: PanicReporting) {
+  let mut guard = PANIC_REPORTING.lock().recover();
+  *guard = options;
+}
+
+/// Information about a panic that occurred in Rust.
+pub struct PanicReport {
+  pub message: String,
+  /// The source location of the panic, such as `src/lib.rs:10:5`.
+  pub location: Option<String>,
+  /// The name of the thread that panicked, if it has one.
+  pub thread: Option<String>,
+  pub backtrace: Option<String>,
+}
+
+impl Display for PanicReport {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let thread = self.thread.as_deref().unwrap_or("<unnamed>");
+    write!(f, "Thread '{thread}' panicked")?;
+    if let Some(location) = &self.location {
+      write!(f, " at {location}")?;
+    }
+    write!(f, ":\n{}", self.message)?;
+    if let Some(backtrace) = &self.backtrace {
+      write!(f, "\n{backtrace}")?;
+    }
+    Ok(())
+  }
+}
+
+impl PanicReport {
+  fn from_hook_info(panic_info: &PanicHookInfo, with_backtrace: bool) -> Self {
+    let payload = panic_info.payload();
+    let message = if let Some(text) = payload.downcast_ref::<&str>() {
+      text.to_string()
+    } else if let Some(text) = payload.downcast_ref::<String>() {
+      text.to_owned()
+    } else {
+      "Box<dyn Any>".to_owned()
+    };
+    PanicReport {
+      message,
+      location: panic_info.location().map(|location| location.to_string()),
+      thread: current().name().map(|name| name.to_owned()),
+      backtrace: with_backtrace.then(capture_backtrace),
+    }
+  }
+}
+
+#[cfg(all(feature = "show-backtrace", not(target_family = "wasm")))]
+fn capture_backtrace() -> String {
+  format!("{:?}", backtrace::Backtrace::new())
+}
+
+#[cfg(not(all(feature = "show-backtrace", not(target_family = "wasm"))))]
+fn capture_backtrace() -> String {
+  std::backtrace::Backtrace::force_capture().to_string()
+}
+
+static PANIC_HOOK_INSTALLED: Once = Once::new();
+
+/// Installs the panic hook that reports panics
+/// according to the current `PanicReporting` options.
+/// The previous hook still runs first, so panics are printed to stderr
+/// in every build mode. The hook is installed only once,
+/// even if the Rust logic is started again on hot restart.
+pub fn install_panic_hook() {
+  PANIC_HOOK_INSTALLED.call_once(|| {
+    let previous_hook = take_hook();
+    set_hook(Box::new(move |panic_info| {
+      previous_hook(panic_info);
+      report_panic(panic_info);
+    }));
+  });
+}
+
+fn report_panic(panic_info: &PanicHookInfo) {
+  let options = *PANIC_REPORTING.lock().recover();
+  let with_backtrace =
+    options.capture_backtrace || cfg!(feature = "show-backtrace");
+  let report = PanicReport::from_hook_info(panic_info, with_backtrace);
+
+  #[cfg(debug_assertions)]
+  {
+    use crate::debug_print;
+    debug_print!("A panic occurred in Rust.\n{}", report);
+  }
+
+  if options.send_to_dart {
+    send_panic_report(report);
+  }
+}
+
+/// Sends a panic report to Dart.
+/// Failures are ignored because there is no place to report them.
+fn send_panic_report(report: PanicReport) {
+  // Tuples are serialized without derive macros,
+  // and the Dart side reads the same layout.
+  let encoded = bincode::serialize(&(
+    report.message,
+    report.location,
+    report.thread,
+    report.backtrace,
+  ));
+  // Queued signals are sent first, because the panic report
+  // should not wait for the batch that might never end.
+  if let Ok(message_bytes) = encoded {
+    let _ = send_immediately(PANIC_ENDPOINT_ID, message_bytes, Vec::new());
+  }
+}
+
+/// Runs a future and catches any panic that occurs while polling it.
+/// The panic is reported through the panic hook,
+/// and `AppError::TaskPanicked` is returned
+/// instead of ending the thread that runs the async runtime.
+/// Note that panics abort the program on the web,
+/// so they cannot be caught there.
+pub fn catch_panic<F: Future>(
+  future: F,
+) -> impl Future<Output = Result<F::Output, AppError>> {
+  CatchPanic {
+    inner: Box::pin(future),
+  }
+}
+
+struct CatchPanic<F> {
+  inner: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for CatchPanic<F> {
+  type Output = Result<F::Output, AppError>;
+
+  fn poll(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Self::Output> {
+    let inner = &mut self.inner;
+    match catch_unwind(AssertUnwindSafe(|| inner.as_mut().poll(cx))) {
+      Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+      Ok(Poll::Pending) => Poll::Pending,
+      Err(_) => Poll::Ready(Err(AppError::TaskPanicked)),
+    }
+  }
+}
@@ -0,0 +1,234 @@
+//! Records signal sessions to a file and replays them,
+//! so that bugs can be reproduced deterministically.
+//!
+//! A recording file starts with the `RINFREC` magic bytes
+//! and a format version byte.
+//! Each entry contains the direction as a byte,
+//! the elapsed time in microseconds as a little-endian `u64`,
+//! the endpoint ID as a little-endian `u32`,
+//! and the message bytes and the binary,
+//! each prefixed with its size as a little-endian `u64`.
+
+use crate::endpoint::OUT_ENDPOINT_ID;
+use crate::traits::GuardRecovery;
+use crate::{AppError, send_dart_signal};
+use std::fs::{File, read};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// Magic bytes and the format version at the start of a recording file.
+const FILE_HEADER: &[u8; 8] = b"RINFREC\x01";
+
+/// How often buffered entries are written to the file.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Recorder {
+  writer: BufWriter<File>,
+  started_at: Instant,
+  flushed_at: Instant,
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+static IS_RECORDING: AtomicBool = AtomicBool::new(false);
+
+/// Which way a recorded signal was going.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordedDirection {
+  DartToRust = 0,
+  RustToDart = 1,
+}
+
+/// A signal in a recording.
+#[derive(Clone, Debug)]
+pub struct RecordedSignal {
+  pub direction: RecordedDirection,
+  /// Time since the recording started.
+  pub elapsed: Duration,
+  pub endpoint_id: u32,
+  pub message_bytes: Vec<u8>,
+  pub binary: Vec<u8>,
+}
+
+/// How fast a recording is replayed.
+#[derive(Clone, Copy)]
+pub enum ReplaySpeed {
+  /// Keeps the original intervals between signals.
+  Original,
+  /// Sends all signals without waiting.
+  Instant,
+}
+
+/// Starts writing all signals in both directions to the file,
+/// replacing any recording in progress.
+/// Signals are buffered and written to the file about once a second,
+/// so most of the recording is kept even if the app crashes.
+/// The rest is written when the recording stops.
+pub fn start_recording(path: &Path) -> Result<(), AppError> {
+  let file = File::create(path).map_err(|_| AppError::CannotAccessRecording)?;
+  let mut writer = BufWriter::new(file);
+  writer
+    .write_all(FILE_HEADER)
+    .map_err(|_| AppError::CannotAccessRecording)?;
+  let mut guard = RECORDER.lock().recover();
+  let now = Instant::now();
+  guard.replace(Recorder {
+    writer,
+    started_at: now,
+    flushed_at: now,
+  });
+  IS_RECORDING.store(true, Ordering::Release);
+  Ok(())
+}
+
+/// Stops the recording in progress, if any.
+pub fn stop_recording() -> Result<(), AppError> {
+  IS_RECORDING.store(false, Ordering::Release);
+  let mut guard = RECORDER.lock().recover();
+  match guard.take() {
+    Some(mut recorder) => recorder
+      .writer
+      .flush()
+      .map_err(|_| AppError::CannotAccessRecording),
+    None => Ok(()),
+  }
+}
+
+/// Writes a signal to the recording in progress, if any.
+/// Output from `debug_print!` is not recorded.
+pub fn record(
+  direction: RecordedDirection,
+  endpoint_id: u32,
+  message_bytes: &[u8],
+  binary: &[u8],
+) {
+  if !IS_RECORDING.load(Ordering::Acquire) || endpoint_id == OUT_ENDPOINT_ID {
+    return;
+  }
+  let mut guard = RECORDER.lock().recover();
+  let Some(recorder) = guard.as_mut() else {
+    return;
+  };
+  let elapsed = recorder.started_at.elapsed().as_micros() as u64;
+  let mut entry = Vec::with_capacity(29 + message_bytes.len() + binary.len());
+  entry.push(direction as u8);
+  entry.extend_from_slice(&elapsed.to_le_bytes());
+  entry.extend_from_slice(&endpoint_id.to_le_bytes());
+  entry.extend_from_slice(&(message_bytes.len() as u64).to_le_bytes());
+  entry.extend_from_slice(message_bytes);
+  entry.extend_from_slice(&(binary.len() as u64).to_le_bytes());
+  entry.extend_from_slice(binary);
+  let mut result = recorder.writer.write_all(&entry);
+  if result.is_ok() && recorder.flushed_at.elapsed() >= FLUSH_INTERVAL {
+    result = recorder.writer.flush();
+    recorder.flushed_at = Instant::now();
+  }
+  if result.is_err() {
+    // Stop recording instead of writing a broken file.
+    IS_RECORDING.store(false, Ordering::Release);
+    guard.take();
+  }
+}
+
+/// Signals loaded from a recording file.
+pub struct Recording {
+  pub signals: Vec<RecordedSignal>,
+}
+
+impl Recording {
+  /// Reads a recording file.
+  pub fn load(path: &Path) -> Result<Self, AppError> {
+    let bytes = read(path).map_err(|_| AppError::CannotAccessRecording)?;
+    let mut reader = EntryReader { bytes: &bytes };
+    if reader.take(FILE_HEADER.len()) != Some(FILE_HEADER) {
+      return Err(AppError::CannotAccessRecording);
+    }
+    let mut signals = Vec::new();
+    while !reader.bytes.is_empty() {
+      let signal = reader
+        .read_signal()
+        .ok_or(AppError::CannotAccessRecording)?;
+      signals.push(signal);
+    }
+    Ok(Self { signals })
+  }
+
+  /// Returns the recorded signals from Rust to Dart,
+  /// which can be compared with the output of a replay.
+  pub fn rust_signals(&self) -> impl Iterator<Item = &RecordedSignal> {
+    self
+      .signals
+      .iter()
+      .filter(|signal| signal.direction == RecordedDirection::RustToDart)
+  }
+
+  /// Feeds the recorded signals from Dart to Rust
+  /// into their receivers, as if they were sent from Dart again.
+  /// This blocks the thread until all signals are sent.
+  pub fn replay(&self, speed: ReplaySpeed) {
+    let started_at = Instant::now();
+    for signal in &self.signals {
+      if signal.direction != RecordedDirection::DartToRust {
+        continue;
+      }
+      if let ReplaySpeed::Original = speed {
+        let waited = started_at.elapsed();
+        if signal.elapsed > waited {
+          sleep(signal.elapsed - waited);
+        }
+      }
+      send_dart_signal(
+        signal.endpoint_id,
+        &signal.message_bytes,
+        signal.binary.clone(),
+      );
+    }
+  }
+}
+
+struct EntryReader<'a> {
+  bytes: &'a [u8],
+}
+
+impl<'a> EntryReader<'a> {
+  fn take(&mut self, size: usize) -> Option<&'a [u8]> {
+    if self.bytes.len() < size {
+      return None;
+    }
+    let (taken, rest) = self.bytes.split_at(size);
+    self.bytes = rest;
+    Some(taken)
+  }
+
+  fn read_u64(&mut self) -> Option<u64> {
+    Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+  }
+
+  fn read_bytes(&mut self) -> Option<Vec<u8>> {
+    let size = usize::try_from(self.read_u64()?).ok()?;
+    Some(self.take(size)?.to_vec())
+  }
+
+  fn read_signal(&mut self) -> Option<RecordedSignal> {
+    let direction = match self.take(1)?[0] {
+      0 => RecordedDirection::DartToRust,
+      1 => RecordedDirection::RustToDart,
+      _ => return None,
+    };
+    let elapsed = Duration::from_micros(self.read_u64()?);
+    let endpoint_id = u32::from_le_bytes(self.take(4)?.try_into().ok()?);
+    let message_bytes = self.read_bytes()?;
+    let binary = self.read_bytes()?;
+    Some(RecordedSignal {
+      direction,
+      elapsed,
+      endpoint_id,
+      message_bytes,
+      binary,
+    })
+  }
+}
@@ -1,12 +1,21 @@
+use crate::panic::catch_panic;
 use crate::traits::GuardRecovery;
+use std::mem::take;
+use std::panic::{AssertUnwindSafe, Location, catch_unwind};
 use std::pin::Pin;
-use std::sync::{Arc, LazyLock, Mutex};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
 use std::task::{Context, Poll, Waker};
 
 // Thread-blocking operations are possible
 // only on non-web platforms.
 #[cfg(not(target_family = "wasm"))]
+use crate::executor::block_on;
+#[cfg(not(target_family = "wasm"))]
+use crate::{LogLevel, LogRecord, debug_print, send_log_record};
+#[cfg(not(target_family = "wasm"))]
 use std::sync::Condvar;
+#[cfg(not(target_family = "wasm"))]
+use std::time::{Duration, Instant};
 
 pub type ShutdownEventsLock = LazyLock<ShutdownEvents>;
 pub static SHUTDOWN_EVENTS: ShutdownEventsLock =
@@ -24,13 +33,533 @@ pub struct ShutdownEvents {
   pub rust_stopped: Event,
 }
 
+impl ShutdownEvents {
+  /// Notifies Rust that Dart has stopped,
+  /// which runs synchronous shutdown hooks,
+  /// cancels all shutdown tokens, and starts async shutdown hooks.
+  /// Hooks of a session run only once,
+  /// even if this is called multiple times.
+  pub fn stop_dart(&self) {
+    let session = HOOK_SESSION.lock().recover().clone();
+    let is_first_stop = session.run_sync_hooks();
+    self.dart_stopped.set();
+    ROOT_TOKEN.lock().recover().cancel();
+    if is_first_stop {
+      session.start_hooks();
+    }
+  }
+
+  /// Clears the shutdown events and replaces the root shutdown token
+  /// and the hook session to prepare for a fresh start.
+  pub fn reset(&self) {
+    self.dart_stopped.clear();
+    #[cfg(not(target_family = "wasm"))]
+    self.rust_stopped.clear();
+    *ROOT_TOKEN.lock().recover() = ShutdownToken::new();
+    *HOOK_SESSION.lock().recover() = Arc::new(HookSession::new());
+  }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl ShutdownEvents {
+  /// Blocks the current thread until the Rust logic stops,
+  /// or until the shutdown deadline passes.
+  pub fn wait_rust_stopped(&self) {
+    let deadline = *SHUTDOWN_DEADLINE.lock().recover();
+    match deadline {
+      Some(timeout) => {
+        if !self.rust_stopped.wait_timeout(timeout) {
+          report_unfinished_hooks();
+        }
+      }
+      None => self.rust_stopped.wait(),
+    }
+  }
+}
+
 /// Retrieves the shutdown receiver that listens for
 /// the Dart runtime's closure.
 /// Awaiting this receiver in the async main Rust function
 /// is necessary to prevent the async runtime in Rust from
 /// finishing immediately.
-pub fn dart_shutdown() -> impl Future<Output = ()> {
-  SHUTDOWN_EVENTS.dart_stopped.wait_async()
+/// This completes after the hooks registered with `on_shutdown`
+/// have finished.
+pub async fn dart_shutdown() {
+  let session = HOOK_SESSION.lock().recover().clone();
+  session.hooks_finished.wait_async().await;
+}
+
+/// Returns a future that completes when the shutdown hooks
+/// of the current session have finished.
+#[cfg(target_family = "wasm")]
+pub(crate) fn hooks_finished() -> EventFuture {
+  HOOK_SESSION.lock().recover().hooks_finished.wait_async()
+}
+
+/// The root of all shutdown tokens,
+/// which is cancelled when Dart stops.
+static ROOT_TOKEN: LazyLock<Mutex<ShutdownToken>> =
+  LazyLock::new(|| Mutex::new(ShutdownToken::new()));
+
+/// Returns a new shutdown token that is cancelled when Dart stops.
+/// Tasks can await `cancelled` on this token
+/// instead of racing `dart_shutdown` by themselves.
+pub fn shutdown_token() -> ShutdownToken {
+  ROOT_TOKEN.lock().recover().child()
+}
+
+/// A token that tells tasks to stop.
+/// Cancelling a token also cancels all of its children,
+/// but not its parent.
+/// Clones of a token share the same cancellation state.
+#[derive(Clone)]
+pub struct ShutdownToken {
+  node: Arc<Mutex<TokenState>>,
+}
+
+struct TokenState {
+  is_cancelled: bool,
+  wakers: Vec<Waker>,
+  children: Vec<Weak<Mutex<TokenState>>>,
+}
+
+impl ShutdownToken {
+  fn new() -> Self {
+    let state = TokenState {
+      is_cancelled: false,
+      wakers: Vec::new(),
+      children: Vec::new(),
+    };
+    ShutdownToken {
+      node: Arc::new(Mutex::new(state)),
+    }
+  }
+
+  /// Creates a child token that is cancelled along with this token.
+  /// If this token is already cancelled, the child is cancelled too.
+  pub fn child(&self) -> ShutdownToken {
+    let child = ShutdownToken::new();
+    let mut guard = self.node.lock().recover();
+    if guard.is_cancelled {
+      child.node.lock().recover().is_cancelled = true;
+    } else {
+      // Children that were dropped are removed.
+      guard
+        .children
+        .retain(|existing| existing.strong_count() > 0);
+      guard.children.push(Arc::downgrade(&child.node));
+    }
+    child
+  }
+
+  /// Cancels this token and all of its children,
+  /// waking up the tasks waiting for them.
+  pub fn cancel(&self) {
+    cancel_node(&self.node);
+  }
+
+  /// Checks whether this token was cancelled.
+  pub fn is_cancelled(&self) -> bool {
+    self.node.lock().recover().is_cancelled
+  }
+
+  /// Creates a future that will be resolved
+  /// when this token is cancelled.
+  pub fn cancelled(&self) -> TokenFuture {
+    TokenFuture {
+      node: self.node.clone(),
+    }
+  }
+}
+
+fn cancel_node(node: &Mutex<TokenState>) {
+  let mut guard = node.lock().recover();
+  if guard.is_cancelled {
+    return;
+  }
+  guard.is_cancelled = true;
+  let wakers = take(&mut guard.wakers);
+  let children = take(&mut guard.children);
+  drop(guard);
+  for waker in wakers {
+    waker.wake();
+  }
+  for child in children.iter().filter_map(Weak::upgrade) {
+    cancel_node(&child);
+  }
+}
+
+/// Future that resolves when a `ShutdownToken` is cancelled.
+pub struct TokenFuture {
+  node: Arc<Mutex<TokenState>>,
+}
+
+impl Future for TokenFuture {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let mut guard = self.node.lock().recover();
+    if guard.is_cancelled {
+      return Poll::Ready(());
+    }
+    let waker = cx.waker();
+    if !guard
+      .wakers
+      .iter()
+      .any(|existing_waker| existing_waker.will_wake(waker))
+    {
+      guard.wakers.push(waker.clone());
+    }
+    Poll::Pending
+  }
+}
+
+type ShutdownHookFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+struct ShutdownHook {
+  priority: i32,
+  /// Where the hook was registered, used for reporting.
+  location: &'static Location<'static>,
+  run: Box<dyn FnOnce() -> ShutdownHookFuture + Send>,
+}
+
+struct SyncShutdownHook {
+  priority: i32,
+  run: Box<dyn FnOnce() + Send>,
+}
+
+struct HookState {
+  /// Whether Dart has stopped in this session.
+  is_stopped: bool,
+  /// Synchronous hooks that have not run yet.
+  sync_registered: Vec<SyncShutdownHook>,
+  /// Hooks that have not started yet.
+  registered: Vec<ShutdownHook>,
+  /// Locations of hooks that have started but not finished yet.
+  unfinished: Vec<&'static Location<'static>>,
+  /// How many groups of hooks are waiting or running.
+  running_groups: usize,
+}
+
+/// Shutdown hooks registered during one run of the Rust logic.
+/// A new session begins whenever the Rust logic starts,
+/// so that the previous Rust logic, which might still be finishing
+/// after Dart's hot restart, never runs or waits for the new hooks.
+struct HookSession {
+  state: Mutex<HookState>,
+  /// Set when Dart stops, which starts local hooks on the web.
+  #[cfg(target_family = "wasm")]
+  stopped: Event,
+  /// Set when all async hooks of this session have finished.
+  hooks_finished: Event,
+}
+
+static HOOK_SESSION: LazyLock<Mutex<Arc<HookSession>>> =
+  LazyLock::new(|| Mutex::new(Arc::new(HookSession::new())));
+
+impl HookSession {
+  fn new() -> Self {
+    let state = HookState {
+      is_stopped: false,
+      sync_registered: Vec::new(),
+      registered: Vec::new(),
+      unfinished: Vec::new(),
+      running_groups: 0,
+    };
+    HookSession {
+      state: Mutex::new(state),
+      #[cfg(target_family = "wasm")]
+      stopped: Event::new(),
+      hooks_finished: Event::new(),
+    }
+  }
+
+  /// Runs all synchronous hooks in order, if Dart has just stopped.
+  /// Returns `false` if Dart had already stopped in this session.
+  fn run_sync_hooks(&self) -> bool {
+    let mut guard = self.state.lock().recover();
+    if guard.is_stopped {
+      return false;
+    }
+    guard.is_stopped = true;
+    let mut hooks = take(&mut guard.sync_registered);
+    drop(guard);
+    // Sorting is stable, so the registration order is kept.
+    hooks.sort_by_key(|hook| -i64::from(hook.priority));
+    for hook in hooks {
+      // A panic is reported by the panic hook,
+      // and shouldn't keep the other hooks from running.
+      let _ = catch_unwind(AssertUnwindSafe(hook.run));
+    }
+    true
+  }
+
+  /// Starts running all async hooks in order,
+  /// without waiting for them to finish.
+  fn start_hooks(self: Arc<Self>) {
+    let mut hooks = take(&mut self.state.lock().recover().registered);
+    // Sorting is stable, so the registration order is kept.
+    hooks.sort_by_key(|hook| -i64::from(hook.priority));
+    // Local hooks on the web wait for this.
+    #[cfg(target_family = "wasm")]
+    self.stopped.set();
+    self.run_group(hooks);
+  }
+
+  /// Starts running a group of async hooks one by one,
+  /// without waiting for them to finish.
+  fn run_group(self: Arc<Self>, hooks: Vec<ShutdownHook>) {
+    let mut guard = self.state.lock().recover();
+    guard
+      .unfinished
+      .extend(hooks.iter().map(|hook| hook.location));
+    guard.running_groups += 1;
+    drop(guard);
+    if hooks.is_empty() {
+      self.finish_group();
+      return;
+    }
+    let session = self.clone();
+    let runner = async move {
+      for hook in hooks {
+        let location = hook.location;
+        run_hook(hook.run).await;
+        let mut guard = session.state.lock().recover();
+        let position = guard
+          .unfinished
+          .iter()
+          .position(|existing| *existing == location);
+        if let Some(index) = position {
+          guard.unfinished.remove(index);
+        }
+      }
+      session.finish_group();
+    };
+    if !spawn_hook_runner(runner) {
+      // Awaiters shouldn't wait forever for hooks that can't run.
+      self.finish_group();
+    }
+  }
+
+  /// Marks a group of hooks as finished,
+  /// letting awaiters of `dart_shutdown` continue after the last one.
+  fn finish_group(&self) {
+    let mut guard = self.state.lock().recover();
+    guard.running_groups = guard.running_groups.saturating_sub(1);
+    if guard.running_groups == 0 {
+      drop(guard);
+      self.hooks_finished.set();
+    }
+  }
+}
+
+/// Runs an async hook, catching any panic
+/// so that the hooks after it still run
+/// and awaiters of `dart_shutdown` are not left waiting.
+async fn run_hook<T: Future<Output = ()>>(run: impl FnOnce() -> T) {
+  if let Ok(future) = catch_unwind(AssertUnwindSafe(run)) {
+    let _ = catch_panic(future).await;
+  }
+}
+
+/// Drives async hooks on a dedicated thread,
+/// because the shutdown path doesn't belong to any async runtime.
+/// Returns `false` if the thread could not be created.
+#[cfg(not(target_family = "wasm"))]
+fn spawn_hook_runner(
+  runner: impl Future<Output = ()> + Send + 'static,
+) -> bool {
+  std::thread::Builder::new()
+    .name("rinf-shutdown-hooks".to_owned())
+    .spawn(move || block_on(runner))
+    .is_ok()
+}
+
+/// Drives async hooks with the event loop of the browser.
+#[cfg(target_family = "wasm")]
+fn spawn_hook_runner(runner: impl Future<Output = ()> + 'static) -> bool {
+  wasm_bindgen_futures::spawn_local(runner);
+  true
+}
+
+/// Registers an async hook that runs when Dart stops,
+/// before `dart_shutdown` completes in the main function.
+/// Hooks run one by one, from the highest priority to the lowest.
+/// Hooks with the same priority run in the order they were registered.
+/// On native platforms, hooks run on a separate thread
+/// outside of the async runtime, so work that needs the runtime
+/// should be spawned on it with a handle and awaited.
+/// If Dart has already stopped, the hook starts right away.
+#[track_caller]
+pub fn on_shutdown<F, T>(priority: i32, hook: F)
+where
+  F: FnOnce() -> T + Send + 'static,
+  T: Future<Output = ()> + Send + 'static,
+{
+  let hook = ShutdownHook {
+    priority,
+    location: Location::caller(),
+    run: Box::new(move || -> ShutdownHookFuture { Box::pin(hook()) }),
+  };
+  let session = HOOK_SESSION.lock().recover().clone();
+  let mut guard = session.state.lock().recover();
+  if !guard.is_stopped {
+    guard.registered.push(hook);
+    return;
+  }
+  drop(guard);
+  session.run_group(vec![hook]);
+}
+
+/// Registers an async hook that doesn't need to be `Send`,
+/// such as one that holds JavaScript objects.
+/// The hook runs when Dart stops, on the thread that registered it,
+/// before `dart_shutdown` completes in the main function.
+/// Local hooks of a thread run one by one,
+/// from the highest priority to the lowest,
+/// alongside the hooks registered with `on_shutdown`.
+/// If Dart has already stopped, the hook starts right away.
+#[cfg(target_family = "wasm")]
+pub fn on_shutdown_local<F, T>(priority: i32, hook: F)
+where
+  F: FnOnce() -> T + 'static,
+  T: Future<Output = ()> + 'static,
+{
+  let hook = LocalShutdownHook {
+    priority,
+    run: Box::new(move || -> LocalShutdownHookFuture { Box::pin(hook()) }),
+  };
+  let session = HOOK_SESSION.lock().recover().clone();
+  LOCAL_HOOKS.with_borrow_mut(|groups| {
+    let group = groups
+      .iter_mut()
+      .find(|(existing, _)| Arc::ptr_eq(existing, &session));
+    if let Some((_, hooks)) = group {
+      hooks.push(hook);
+      return;
+    }
+    // The first local hook of this thread in the session
+    // starts a group that waits for Dart to stop.
+    session.state.lock().recover().running_groups += 1;
+    groups.push((session.clone(), vec![hook]));
+    wasm_bindgen_futures::spawn_local(run_local_hooks(session));
+  });
+}
+
+#[cfg(target_family = "wasm")]
+type LocalShutdownHookFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+#[cfg(target_family = "wasm")]
+struct LocalShutdownHook {
+  priority: i32,
+  run: Box<dyn FnOnce() -> LocalShutdownHookFuture>,
+}
+
+#[cfg(target_family = "wasm")]
+type LocalHookGroup = (Arc<HookSession>, Vec<LocalShutdownHook>);
+
+#[cfg(target_family = "wasm")]
+thread_local! {
+  /// Local hooks of this thread, grouped by their sessions.
+  static LOCAL_HOOKS: std::cell::RefCell<Vec<LocalHookGroup>> =
+    const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Runs the local hooks of this thread in the session
+/// after Dart stops.
+#[cfg(target_family = "wasm")]
+async fn run_local_hooks(session: Arc<HookSession>) {
+  session.stopped.wait_async().await;
+  let mut hooks = LOCAL_HOOKS.with_borrow_mut(|groups| {
+    let position = groups
+      .iter()
+      .position(|(existing, _)| Arc::ptr_eq(existing, &session));
+    match position {
+      Some(index) => groups.remove(index).1,
+      None => Vec::new(),
+    }
+  });
+  // Sorting is stable, so the registration order is kept.
+  hooks.sort_by_key(|hook| -i64::from(hook.priority));
+  for hook in hooks {
+    run_hook(hook.run).await;
+  }
+  session.finish_group();
+}
+
+/// Registers a synchronous hook that runs right when Dart stops,
+/// before any async hook.
+/// On the web, this runs inside the `pagehide` event
+/// of the browser, which is the last chance to do work
+/// before the page is unloaded, such as saving state.
+/// Because browsers don't wait for async work on unload,
+/// this is only best-effort and the hook should finish quickly.
+/// Hooks run from the highest priority to the lowest.
+/// If Dart has already stopped, the hook runs immediately.
+pub fn on_shutdown_sync<F>(priority: i32, hook: F)
+where
+  F: FnOnce() + Send + 'static,
+{
+  let session = HOOK_SESSION.lock().recover().clone();
+  let mut guard = session.state.lock().recover();
+  if guard.is_stopped {
+    drop(guard);
+    hook();
+    return;
+  }
+  guard.sync_registered.push(SyncShutdownHook {
+    priority,
+    run: Box::new(hook),
+  });
+}
+
+/// How long the native side waits for the Rust logic to stop.
+#[cfg(not(target_family = "wasm"))]
+static SHUTDOWN_DEADLINE: Mutex<Option<Duration>> =
+  Mutex::new(Some(Duration::from_secs(5)));
+
+/// Sets how long the native side waits for the Rust logic to stop
+/// when Dart stops, including the time for shutdown hooks.
+/// After the deadline, the app exits without waiting further,
+/// and a warning for each hook that didn't finish
+/// is sent as a log record.
+/// The default deadline is 5 seconds,
+/// and `None` makes it wait without a deadline.
+#[cfg(not(target_family = "wasm"))]
+pub fn set_shutdown_deadline(deadline: Option<Duration>) {
+  *SHUTDOWN_DEADLINE.lock().recover() = deadline;
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn report_unfinished_hooks() {
+  let session = HOOK_SESSION.lock().recover().clone();
+  let guard = session.state.lock().recover();
+  let locations = guard
+    .unfinished
+    .iter()
+    .chain(guard.registered.iter().map(|hook| &hook.location));
+  for location in locations {
+    debug_print!(
+      "Shutdown hook registered at {location} did not finish before the deadline"
+    );
+    send_log_record(LogRecord {
+      level: LogLevel::Warn,
+      target: "rinf".to_owned(),
+      message: "Shutdown hook did not finish before the deadline".to_owned(),
+      file: Some(location.file().to_owned()),
+      line: Some(location.line()),
+      fields: Vec::new(),
+    });
+  }
+  debug_print!("Rust logic did not stop before the shutdown deadline");
+  send_log_record(LogRecord {
+    level: LogLevel::Warn,
+    target: "rinf".to_owned(),
+    message: "Rust logic did not stop before the shutdown deadline".to_owned(),
+    file: None,
+    line: None,
+    fields: Vec::new(),
+  });
 }
 
 /// Synchronization primitive that allows
@@ -65,10 +594,7 @@ impl Event {
       inner: self.inner.clone(),
     }
   }
-}
 
-#[cfg(not(target_family = "wasm"))]
-impl Event {
   /// Sets the flag to `true` and notifies all waiting threads.
   /// This will wake up any threads or async tasks.
   pub fn set(&self) {
@@ -77,6 +603,7 @@ impl Event {
     guard.session += 1; // Increment the session count
 
     // Wake all threads and async tasks when the event is set
+    #[cfg(not(target_family = "wasm"))]
     self.condvar.notify_all();
     for waker in guard.wakers.drain(..) {
       waker.wake();
@@ -91,7 +618,10 @@ impl Event {
     let mut guard = self.inner.lock().recover();
     guard.flag = false; // Clear the flag
   }
+}
 
+#[cfg(not(target_family = "wasm"))]
+impl Event {
   /// Blocks the current thread until the flag is set to `true`.
   /// If the flag is already set,
   /// this method will return immediately.
@@ -109,6 +639,28 @@ impl Event {
       guard = self.condvar.wait(guard).recover();
     }
   }
+
+  /// Blocks the current thread until the flag is set to `true`
+  /// or the timeout passes.
+  /// Returns `false` if the timeout passed first.
+  pub fn wait_timeout(&self, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    let mut guard = self.inner.lock().recover();
+    let started_session = guard.session;
+    loop {
+      if guard.flag || guard.session != started_session {
+        return true;
+      }
+      let now = Instant::now();
+      if now >= deadline {
+        return false;
+      }
+      guard = match self.condvar.wait_timeout(guard, deadline - now) {
+        Ok((inner, _)) => inner,
+        Err(poisoned) => poisoned.into_inner().0,
+      };
+    }
+  }
 }
 
 /// Internal state for the `Event` synchronization primitive.
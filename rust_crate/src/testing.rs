@@ -0,0 +1,268 @@
+//! Utilities for testing Rust logic without Flutter.
+//! After `install_capture` is called, Rust signals are captured
+//! in memory instead of being sent to Dart,
+//! and Dart signals can be injected with `inject`.
+//!
+//! The capture is shared by the whole process,
+//! so tests that depend on the order of captured signals
+//! should not run in parallel with each other.
+//!
+//! Tests without an async runtime can wait for futures,
+//! such as the one from `next_rust_signal`, with `block_on`.
+
+use crate::batch::QueuedSignal;
+#[cfg(not(target_family = "wasm"))]
+pub use crate::executor::{block_on, block_on_timeout};
+use crate::lifecycle::broadcast_lifecycle_event;
+#[cfg(all(feature = "recording", not(target_family = "wasm")))]
+use crate::recording::Recording;
+use crate::traits::GuardRecovery;
+use crate::{
+  AppError, DartSignalId, LifecycleEvent, OUT_ENDPOINT_ID, RustSignalId,
+  deserialize, send_dart_signal, serialize,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll, Waker};
+
+/// A Rust signal that was captured instead of being sent to Dart.
+#[derive(Clone, Debug)]
+pub struct CapturedSignal {
+  pub endpoint_id: u32,
+  pub message_bytes: Vec<u8>,
+  pub binary: Vec<u8>,
+}
+
+impl CapturedSignal {
+  /// Checks whether this is a signal of the given type.
+  pub fn is<T: RustSignalId>(&self) -> bool {
+    self.endpoint_id == T::ENDPOINT_ID
+  }
+
+  /// Decodes the message of this signal as the given type.
+  pub fn decode<T>(&self) -> Result<T, AppError>
+  where
+    T: RustSignalId + DeserializeOwned,
+  {
+    if !self.is::<T>() {
+      return Err(AppError::CannotDecodeMessage);
+    }
+    deserialize(&self.message_bytes).map_err(|_| AppError::CannotDecodeMessage)
+  }
+}
+
+/// This contains a captured message from Rust,
+/// just like the signal pack that Dart receives.
+pub struct RustSignalPack<T> {
+  /// The message instance.
+  pub message: T,
+  /// Binary data included in the signal.
+  pub binary: Vec<u8>,
+}
+
+struct CaptureState {
+  /// All captured signals in the order they were sent.
+  history: Vec<CapturedSignal>,
+  /// Signals that were not yet received by `next_rust_signal`.
+  unread: Vec<CapturedSignal>,
+  /// Tasks waiting for a new signal,
+  /// with one slot for each pending `next_rust_signal` future.
+  wakers: BTreeMap<u64, Waker>,
+  /// The key of the waker slot for the next pending future.
+  next_waker_key: u64,
+}
+
+static CAPTURE_STATE: Mutex<CaptureState> = Mutex::new(CaptureState {
+  history: Vec::new(),
+  unread: Vec::new(),
+  wakers: BTreeMap::new(),
+  next_waker_key: 0,
+});
+
+static IS_CAPTURING: AtomicBool = AtomicBool::new(false);
+
+/// Starts capturing Rust signals in memory instead of sending them to Dart.
+/// Previously captured signals are cleared.
+/// Output of `debug_print!` is still printed to stdout.
+pub fn install_capture() {
+  clear_captured();
+  IS_CAPTURING.store(true, Ordering::Release);
+}
+
+/// Clears all captured signals.
+pub fn clear_captured() {
+  let mut guard = CAPTURE_STATE.lock().recover();
+  guard.history.clear();
+  guard.unread.clear();
+}
+
+/// Returns all captured signals in the order they were sent,
+/// including the ones already received by `next_rust_signal`.
+pub fn captured_signals() -> Vec<CapturedSignal> {
+  CAPTURE_STATE.lock().recover().history.clone()
+}
+
+/// Returns the endpoint IDs of all captured signals
+/// in the order they were sent.
+/// This is handy for asserting on the sequence of signals
+/// along with `endpoint_id_of`.
+pub fn captured_sequence() -> Vec<u32> {
+  let guard = CAPTURE_STATE.lock().recover();
+  guard
+    .history
+    .iter()
+    .map(|signal| signal.endpoint_id)
+    .collect()
+}
+
+/// Checks whether the captured signals are the same as
+/// the Rust signals in the recording, in the same order.
+/// After replaying a recording, this works as a snapshot test
+/// of the Rust logic.
+#[cfg(all(feature = "recording", not(target_family = "wasm")))]
+pub fn matches_recording(recording: &Recording) -> bool {
+  let guard = CAPTURE_STATE.lock().recover();
+  guard.history.len() == recording.rust_signals().count()
+    && guard.history.iter().zip(recording.rust_signals()).all(
+      |(captured, recorded)| {
+        captured.endpoint_id == recorded.endpoint_id
+          && captured.message_bytes == recorded.message_bytes
+          && captured.binary == recorded.binary
+      },
+    )
+}
+
+/// Returns the endpoint ID of a Rust signal type.
+pub fn endpoint_id_of<T: RustSignalId>() -> u32 {
+  T::ENDPOINT_ID
+}
+
+/// Sends a Dart signal to its receiver in Rust,
+/// as if it was sent from Dart.
+/// Receivers from `get_dart_signal_receiver` get this signal.
+pub fn inject<T>(message: T, binary: Vec<u8>) -> Result<(), AppError>
+where
+  T: DartSignalId + Serialize,
+{
+  let message_bytes =
+    serialize(&message).map_err(|_| AppError::CannotEncodeMessage)?;
+  send_dart_signal(T::ENDPOINT_ID, &message_bytes, binary);
+  Ok(())
+}
+
+/// Sends a lifecycle event to receivers from `lifecycle`,
+/// as if it was reported by the Flutter app.
+pub fn inject_lifecycle(event: LifecycleEvent) {
+  broadcast_lifecycle_event(event);
+}
+
+/// Waits for the next captured Rust signal of the given type.
+/// Signals of other types are left for other calls.
+/// The capture should be installed first with `install_capture`.
+pub fn next_rust_signal<T>()
+-> impl Future<Output = Result<RustSignalPack<T>, AppError>>
+where
+  T: RustSignalId + DeserializeOwned,
+{
+  NextSignal {
+    endpoint_id: T::ENDPOINT_ID,
+    waker_key: None,
+  }
+  .into_decoded()
+}
+
+/// Stores a Rust signal if the capture is installed.
+/// Returns the signal back if it should be sent to Dart instead.
+pub(crate) fn capture(signal: QueuedSignal) -> Option<QueuedSignal> {
+  if !IS_CAPTURING.load(Ordering::Acquire) {
+    return Some(signal);
+  }
+  if signal.endpoint_id == OUT_ENDPOINT_ID {
+    println!("{}", String::from_utf8_lossy(&signal.binary));
+    return None;
+  }
+  let signal = CapturedSignal {
+    endpoint_id: signal.endpoint_id,
+    message_bytes: signal.message_bytes,
+    binary: signal.binary,
+  };
+  let mut guard = CAPTURE_STATE.lock().recover();
+  guard.history.push(signal.clone());
+  guard.unread.push(signal);
+  let wakers = std::mem::take(&mut guard.wakers);
+  drop(guard);
+  wakers.into_values().for_each(Waker::wake);
+  None
+}
+
+/// A future that completes when a signal with the endpoint ID is captured.
+struct NextSignal {
+  endpoint_id: u32,
+  /// The key of the waker slot of this future, if it has one.
+  waker_key: Option<u64>,
+}
+
+impl NextSignal {
+  async fn into_decoded<T>(self) -> Result<RustSignalPack<T>, AppError>
+  where
+    T: DeserializeOwned,
+  {
+    let signal = self.await;
+    let message = deserialize(&signal.message_bytes)
+      .map_err(|_| AppError::CannotDecodeMessage)?;
+    Ok(RustSignalPack {
+      message,
+      binary: signal.binary,
+    })
+  }
+}
+
+impl Future for NextSignal {
+  type Output = CapturedSignal;
+
+  fn poll(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Self::Output> {
+    let mut guard = CAPTURE_STATE.lock().recover();
+    let position = guard
+      .unread
+      .iter()
+      .position(|signal| signal.endpoint_id == self.endpoint_id);
+    match position {
+      Some(index) => {
+        if let Some(waker_key) = self.waker_key.take() {
+          guard.wakers.remove(&waker_key);
+        }
+        Poll::Ready(guard.unread.remove(index))
+      }
+      None => {
+        // Keep only the latest waker of this future,
+        // no matter how many times it's polled.
+        let waker_key = match self.waker_key {
+          Some(waker_key) => waker_key,
+          None => {
+            let waker_key = guard.next_waker_key;
+            guard.next_waker_key += 1;
+            self.waker_key = Some(waker_key);
+            waker_key
+          }
+        };
+        guard.wakers.insert(waker_key, cx.waker().to_owned());
+        Poll::Pending
+      }
+    }
+  }
+}
+
+impl Drop for NextSignal {
+  fn drop(&mut self) {
+    if let Some(waker_key) = self.waker_key {
+      CAPTURE_STATE.lock().recover().wakers.remove(&waker_key);
+    }
+  }
+}
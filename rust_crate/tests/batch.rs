@@ -0,0 +1,85 @@
+use rinf::testing::{captured_sequence, captured_signals, install_capture};
+use rinf::{batch, flush, send_rust_signal};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::channel;
+use std::thread::spawn;
+
+/// Tests share the captured signals and the batch queue,
+/// so they run one at a time.
+static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+fn send(endpoint_id: u32, message_bytes: Vec<u8>) {
+  let _ = send_rust_signal(endpoint_id, message_bytes, Vec::new());
+}
+
+#[test]
+fn direct_sends_keep_the_order() {
+  let _test_guard = TEST_LOCK.lock();
+  install_capture();
+
+  // Signals sent after a batch arrive after it.
+  batch(|| {
+    send(16, Vec::new());
+    send(17, Vec::new());
+  });
+  send(18, Vec::new());
+  assert_eq!(captured_sequence(), vec![16, 17, 18]);
+
+  // Another thread keeps flushing while this thread
+  // switches between queued and direct sends.
+  install_capture();
+  let is_done = AtomicBool::new(false);
+  std::thread::scope(|scope| {
+    scope.spawn(|| {
+      while !is_done.load(Ordering::Acquire) {
+        let _ = flush();
+      }
+    });
+    let mut number = 0_u32;
+    while number < 10_000 {
+      batch(|| {
+        for _ in 0..99 {
+          send(16, number.to_le_bytes().to_vec());
+          number += 1;
+        }
+      });
+      send(16, number.to_le_bytes().to_vec());
+      number += 1;
+    }
+    is_done.store(true, Ordering::Release);
+  });
+  let numbers: Vec<Vec<u8>> = captured_signals()
+    .into_iter()
+    .map(|signal| signal.message_bytes)
+    .collect();
+  let expected: Vec<Vec<u8>> = (0..10_000_u32)
+    .map(|number| number.to_le_bytes().to_vec())
+    .collect();
+  assert_eq!(numbers, expected);
+}
+
+#[test]
+fn batches_do_not_hold_other_threads() {
+  let _test_guard = TEST_LOCK.lock();
+  install_capture();
+
+  let (opened_sender, opened_receiver) = channel();
+  let (sent_sender, sent_receiver) = channel();
+  let batching = spawn(move || {
+    batch(|| {
+      send(16, Vec::new());
+      let _ = opened_sender.send(());
+      let _ = sent_receiver.recv();
+    });
+  });
+
+  // This thread is not inside the batch,
+  // so its signal is sent while the batch is still open.
+  let _ = opened_receiver.recv();
+  send(17, Vec::new());
+  assert_eq!(captured_sequence(), vec![17]);
+  let _ = sent_sender.send(());
+  let _ = batching.join();
+  assert_eq!(captured_sequence(), vec![17, 16]);
+}
@@ -0,0 +1,96 @@
+use rinf::testing::block_on_timeout;
+use rinf::{SignalReceiver, signal_channel};
+use std::pin::pin;
+use std::task::{Context, Waker};
+use std::thread::scope;
+use std::time::Duration;
+
+const ROUNDS: usize = 200;
+const MESSAGES_PER_ROUND: usize = 1_000;
+
+/// Receives the next message on the current thread.
+/// Gives up after a while, so that a lost wakeup fails the test
+/// instead of hanging it.
+fn recv_blocking(
+  receiver: &SignalReceiver<usize>,
+) -> Result<Option<usize>, ()> {
+  block_on_timeout(receiver.recv(), Duration::from_secs(5)).ok_or(())
+}
+
+/// Receives messages until the receiver is replaced,
+/// and returns how many were received.
+fn recv_until_replaced(receiver: &SignalReceiver<usize>) -> Result<usize, ()> {
+  let mut received = 0;
+  while recv_blocking(receiver)?.is_some() {
+    received += 1;
+  }
+  Ok(received)
+}
+
+/// Message sent after the old receiver is done,
+/// which tells the new receiver to stop.
+const LAST_MESSAGE: usize = usize::MAX;
+
+#[test]
+fn clone_while_receiving() {
+  for _ in 0..ROUNDS {
+    let (sender, receiver) = signal_channel::<usize>();
+    let (new_count, old_count) = scope(|s| {
+      let sending = s.spawn(|| {
+        for i in 0..MESSAGES_PER_ROUND {
+          sender.send(i);
+        }
+      });
+      // The original receiver waits on its own thread
+      // while this thread replaces it with a clone.
+      let old_receiving = s.spawn(|| recv_until_replaced(&receiver));
+      let new_receiver = receiver.clone();
+      let new_receiving = s.spawn(move || {
+        let mut received = 0;
+        loop {
+          match recv_blocking(&new_receiver)? {
+            Some(LAST_MESSAGE) => return Ok(received),
+            Some(_) => received += 1,
+            None => return Err(()),
+          }
+        }
+      });
+      let old_count = old_receiving.join().unwrap_or(Err(()));
+      let _ = sending.join();
+      sender.send(LAST_MESSAGE);
+      let new_count = new_receiving.join().unwrap_or(Err(()));
+      (new_count, old_count)
+    });
+    // Every message is received exactly once,
+    // and neither receiver misses a wakeup.
+    assert_eq!(
+      old_count.and_then(|old| Ok(old + new_count?)),
+      Ok(MESSAGES_PER_ROUND)
+    );
+    assert_eq!(recv_blocking(&receiver), Ok(None));
+  }
+}
+
+#[test]
+fn clone_while_polling() {
+  for _ in 0..ROUNDS * 10 {
+    let (sender, receiver) = signal_channel::<usize>();
+    let new_msg = scope(|s| {
+      // The original receiver keeps polling without sleeping,
+      // so that the clone often happens in the middle of a poll.
+      let old_receiving = s.spawn(|| {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut future = pin!(receiver.recv());
+        while future.as_mut().poll(&mut cx).is_pending() {}
+      });
+      let new_receiver = receiver.clone();
+      let new_receiving = s.spawn(move || recv_blocking(&new_receiver));
+      let _ = old_receiving.join();
+      sender.send(LAST_MESSAGE);
+      new_receiving.join().unwrap_or(Err(()))
+    });
+    // The new receiver is woken up even if the original receiver
+    // registered its waker after the clone.
+    assert_eq!(new_msg, Ok(Some(LAST_MESSAGE)));
+  }
+}
@@ -0,0 +1,85 @@
+use rinf::{AppError, from_json, to_json};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Wallet {
+  balance: u64,
+  debt: i128,
+  supply: u128,
+  small: i64,
+  history: Vec<u64>,
+  limit: Option<u64>,
+  owners: BTreeMap<u64, String>,
+  last: Transfer,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Transfer {
+  Pending,
+  Sent(u64),
+  Failed { code: u32, amount: u128 },
+}
+
+fn sample_wallet() -> Wallet {
+  Wallet {
+    balance: u64::MAX,
+    debt: i128::MIN,
+    supply: u128::MAX,
+    small: -1,
+    history: vec![0, 9007199254740993],
+    limit: Some(u64::MAX - 1),
+    owners: BTreeMap::from([(18446744073709551615, "me".to_owned())]),
+    last: Transfer::Sent(1),
+  }
+}
+
+/// Serde's default JSON representation, as `serde_json` writes it.
+static WALLET_JSON: &str = concat!(
+  r#"{"balance":18446744073709551615,"#,
+  r#""debt":-170141183460469231731687303715884105728,"#,
+  r#""supply":340282366920938463463374607431768211455,"#,
+  r#""small":-1,"history":[0,9007199254740993],"#,
+  r#""limit":18446744073709551614,"#,
+  r#""owners":{"18446744073709551615":"me"},"last":{"Sent":1}}"#,
+);
+
+#[test]
+fn output_matches_serde_json() -> Result<(), AppError> {
+  let json = to_json(&sample_wallet())?;
+  assert_eq!(json, WALLET_JSON);
+  Ok(())
+}
+
+#[test]
+fn json_is_read_back_exactly() -> Result<(), AppError> {
+  let wallet: Wallet = from_json(WALLET_JSON)?;
+  assert_eq!(wallet, sample_wallet());
+  assert_eq!(to_json(&wallet)?, WALLET_JSON);
+  Ok(())
+}
+
+#[test]
+fn enum_variants_round_trip() -> Result<(), AppError> {
+  let transfers = vec![
+    Transfer::Pending,
+    Transfer::Failed {
+      code: 7,
+      amount: u128::MAX,
+    },
+  ];
+  let json = to_json(&transfers)?;
+  assert_eq!(
+    json,
+    r#"["Pending",{"Failed":{"code":7,"amount":340282366920938463463374607431768211455}}]"#
+  );
+  let decoded: Vec<Transfer> = from_json(&json)?;
+  assert_eq!(decoded, transfers);
+  Ok(())
+}
+
+#[test]
+fn invalid_json_is_rejected() {
+  assert!(from_json::<u64>("\"1\"").is_err());
+  assert!(from_json::<u64>("1 2").is_err());
+}
@@ -0,0 +1,111 @@
+use rinf::testing::{
+  clear_captured, inject, install_capture, matches_recording,
+};
+use rinf::{
+  AppError, DartSignalEndpoint, DartSignalId, RecordedDirection, Recording,
+  ReplaySpeed, RustSignalId, deserialize, send_rust_signal, serialize,
+  start_recording, stop_recording,
+};
+use serde::{Deserialize, Serialize};
+use std::env::temp_dir;
+use std::fs::{remove_file, write};
+use std::path::PathBuf;
+
+/// A Dart signal, implemented by hand
+/// because this crate has no endpoint table from `rinf gen`.
+#[derive(Serialize, Deserialize)]
+struct Ping {
+  number: i32,
+}
+
+impl DartSignalId for Ping {
+  const ENDPOINT_ID: u32 = 16;
+}
+
+/// A Rust signal that answers `Ping`.
+#[derive(Serialize, Deserialize)]
+struct Pong {
+  number: i32,
+}
+
+impl RustSignalId for Pong {
+  const ENDPOINT_ID: u32 = 17;
+}
+
+/// Answers each `Ping` with a `Pong`, like the Rust logic of an app.
+fn handle_ping(message_bytes: &[u8], binary: Vec<u8>) {
+  let Ok(ping) = deserialize::<Ping>(message_bytes) else {
+    return;
+  };
+  let pong = Pong {
+    number: ping.number * 2,
+  };
+  if let Ok(message_bytes) = serialize(&pong) {
+    let _ = send_rust_signal(Pong::ENDPOINT_ID, message_bytes, binary);
+  }
+}
+
+rinf::inventory::submit! {
+  DartSignalEndpoint {
+    id: Ping::ENDPOINT_ID,
+    name: "Ping",
+    handler: handle_ping,
+  }
+}
+
+/// Returns a path in the temporary directory
+/// that no other test process uses.
+fn temp_path(name: &str) -> PathBuf {
+  temp_dir().join(format!("rinf-{}-{}.rec", name, std::process::id()))
+}
+
+#[test]
+fn record_load_and_replay() -> Result<(), AppError> {
+  let path = temp_path("session");
+  install_capture();
+  start_recording(&path)?;
+  inject(Ping { number: 1 }, vec![7])?;
+  inject(Ping { number: 5 }, Vec::new())?;
+  stop_recording()?;
+
+  let recording = Recording::load(&path);
+  let _ = remove_file(&path);
+  let recording = recording?;
+  let directions: Vec<RecordedDirection> = recording
+    .signals
+    .iter()
+    .map(|signal| signal.direction)
+    .collect();
+  assert_eq!(
+    directions,
+    vec![
+      RecordedDirection::DartToRust,
+      RecordedDirection::RustToDart,
+      RecordedDirection::DartToRust,
+      RecordedDirection::RustToDart,
+    ]
+  );
+
+  // Replaying the Dart signals makes the Rust logic
+  // send the same Rust signals again.
+  clear_captured();
+  recording.replay(ReplaySpeed::Instant);
+  assert!(matches_recording(&recording));
+  Ok(())
+}
+
+#[test]
+fn unknown_direction_is_rejected() {
+  let path = temp_path("direction");
+  let mut bytes = b"RINFREC\x01".to_vec();
+  bytes.push(2);
+  bytes.extend_from_slice(&0_u64.to_le_bytes());
+  bytes.extend_from_slice(&16_u32.to_le_bytes());
+  bytes.extend_from_slice(&0_u64.to_le_bytes());
+  bytes.extend_from_slice(&0_u64.to_le_bytes());
+  let written = write(&path, bytes);
+  let loaded = Recording::load(&path);
+  let _ = remove_file(&path);
+  assert!(written.is_ok());
+  assert!(matches!(loaded, Err(AppError::CannotAccessRecording)));
+}
@@ -0,0 +1,28 @@
+use rinf::testing::block_on_timeout;
+use rinf::{
+  dart_shutdown, on_shutdown, set_shutdown_deadline, stop_rust_logic,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[test]
+fn hooks_run_once_without_awaiters() {
+  static RUN_COUNT: AtomicUsize = AtomicUsize::new(0);
+  on_shutdown(0, || async {
+    RUN_COUNT.fetch_add(1, Ordering::SeqCst);
+  });
+
+  // No Rust logic is running here,
+  // so stopping gives up after the deadline.
+  set_shutdown_deadline(Some(Duration::from_millis(300)));
+  stop_rust_logic();
+  stop_rust_logic();
+  assert_eq!(RUN_COUNT.load(Ordering::SeqCst), 1);
+
+  // Hooks are run by the shutdown path itself,
+  // and every awaiter completes once they have finished.
+  let first = block_on_timeout(dart_shutdown(), Duration::from_secs(5));
+  let second = block_on_timeout(dart_shutdown(), Duration::from_secs(5));
+  assert!(first.is_some() && second.is_some());
+  assert_eq!(RUN_COUNT.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,27 @@
+use rinf::{
+  on_shutdown, on_shutdown_sync, set_shutdown_deadline, stop_rust_logic,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+#[test]
+fn hooks_registered_after_stop_still_run() {
+  set_shutdown_deadline(Some(Duration::from_millis(300)));
+  stop_rust_logic();
+
+  // Synchronous hooks run immediately.
+  static RUN_COUNT: AtomicUsize = AtomicUsize::new(0);
+  on_shutdown_sync(0, || {
+    RUN_COUNT.fetch_add(1, Ordering::SeqCst);
+  });
+  assert_eq!(RUN_COUNT.load(Ordering::SeqCst), 1);
+
+  // Async hooks start right away instead of being left behind.
+  let (sender, receiver) = channel();
+  on_shutdown(0, move || async move {
+    let _ = sender.send(());
+  });
+  let received = receiver.recv_timeout(Duration::from_secs(5));
+  assert!(received.is_ok());
+}
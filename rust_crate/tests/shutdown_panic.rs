@@ -0,0 +1,26 @@
+use rinf::testing::block_on_timeout;
+use rinf::{
+  dart_shutdown, on_shutdown, set_shutdown_deadline, stop_rust_logic,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[test]
+fn panicking_hook_does_not_block_shutdown() {
+  static RUN_COUNT: AtomicUsize = AtomicUsize::new(0);
+  on_shutdown(1, || async {
+    panic!("Shutdown hook failed");
+  });
+  on_shutdown(0, || async {
+    RUN_COUNT.fetch_add(1, Ordering::SeqCst);
+  });
+
+  set_shutdown_deadline(Some(Duration::from_millis(300)));
+  stop_rust_logic();
+
+  // The hook after the panicking one still runs,
+  // and awaiters are not left waiting.
+  let finished = block_on_timeout(dart_shutdown(), Duration::from_secs(5));
+  assert!(finished.is_some());
+  assert_eq!(RUN_COUNT.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,81 @@
+use rinf::testing::{
+  block_on, captured_sequence, endpoint_id_of, inject, install_capture,
+  next_rust_signal,
+};
+use rinf::{
+  AppError, DartSignalEndpoint, DartSignalId, RustSignalId, deserialize,
+  send_rust_signal, serialize,
+};
+use serde::{Deserialize, Serialize};
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+/// A Dart signal, implemented by hand
+/// because this crate has no endpoint table from `rinf gen`.
+#[derive(Serialize, Deserialize)]
+struct Ping {
+  number: i32,
+}
+
+impl DartSignalId for Ping {
+  const ENDPOINT_ID: u32 = 16;
+}
+
+/// A Rust signal that answers `Ping`.
+#[derive(Serialize, Deserialize)]
+struct Pong {
+  number: i32,
+}
+
+impl RustSignalId for Pong {
+  const ENDPOINT_ID: u32 = 17;
+}
+
+/// Answers each `Ping` with a `Pong`, like the Rust logic of an app.
+fn handle_ping(message_bytes: &[u8], binary: Vec<u8>) {
+  let Ok(ping) = deserialize::<Ping>(message_bytes) else {
+    return;
+  };
+  let pong = Pong {
+    number: ping.number + 1,
+  };
+  if let Ok(message_bytes) = serialize(&pong) {
+    let _ = send_rust_signal(Pong::ENDPOINT_ID, message_bytes, binary);
+  }
+}
+
+rinf::inventory::submit! {
+  DartSignalEndpoint {
+    id: Ping::ENDPOINT_ID,
+    name: "Ping",
+    handler: handle_ping,
+  }
+}
+
+#[test]
+fn inject_and_capture() -> Result<(), AppError> {
+  install_capture();
+
+  // A pending future can be polled many times
+  // before the signal arrives.
+  let mut cx = Context::from_waker(Waker::noop());
+  let mut waiting = pin!(next_rust_signal::<Pong>());
+  for _ in 0..1_000 {
+    assert!(waiting.as_mut().poll(&mut cx).is_pending());
+  }
+  inject(Ping { number: 1 }, vec![7])?;
+  let Poll::Ready(first) = waiting.as_mut().poll(&mut cx) else {
+    return Err(AppError::CannotDecodeMessage);
+  };
+  let first = first?;
+  assert_eq!(first.message.number, 2);
+  assert_eq!(first.binary, vec![7]);
+
+  inject(Ping { number: 10 }, Vec::new())?;
+  let second = block_on(next_rust_signal::<Pong>())?;
+  assert_eq!(second.message.number, 11);
+
+  let pong_id = endpoint_id_of::<Pong>();
+  assert_eq!(captured_sequence(), vec![pong_id, pong_id]);
+  Ok(())
+}
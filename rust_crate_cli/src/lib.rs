@@ -1,3 +1,3 @@
 mod tool;
 
-pub use tool::{SetupError, run_command};
+pub use tool::{SetupError, report_error, run_command};
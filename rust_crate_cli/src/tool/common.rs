@@ -1,9 +1,8 @@
-use owo_colors::OwoColorize;
-
 use crate::SetupError;
+use crate::tool::report_warning;
 use std::ffi::OsStr;
 use std::net::ToSocketAddrs;
-use std::path::PathBuf;
+use std::path::Path;
 use std::process::Output;
 
 #[cfg(target_family = "windows")]
@@ -21,7 +20,7 @@ pub fn check_internet_connection() -> bool {
 #[macro_export]
 macro_rules! dimmedln {
   ($($arg:tt)*) => {
-      println!("{}", owo_colors::OwoColorize::dimmed(&format!($($arg)*)));
+      $crate::tool::report_info(&format!($($arg)*));
   };
 }
 
@@ -29,7 +28,7 @@ pub trait CleanFileName {
   fn clean_file_name(&self) -> Result<String, SetupError>;
 }
 
-impl CleanFileName for PathBuf {
+impl CleanFileName for Path {
   fn clean_file_name(&self) -> Result<String, SetupError> {
     let file_name = self
       .file_name()
@@ -49,7 +48,7 @@ impl CaptureError for Output {
     if self.status.success() {
       Ok(())
     } else {
-      eprintln!("{}", String::from_utf8_lossy(&self.stderr).red());
+      report_warning(&String::from_utf8_lossy(&self.stderr));
       Err(SetupError::SubprocessError)
     }
   }
@@ -1,27 +1,92 @@
 use crate::SetupError;
-use serde::Deserialize;
-use serde_yml::{Value, from_str, from_value};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use serde_norway::{Value, from_str, from_value};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::fs::read_to_string;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Rinf message configuration structure.
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct RinfConfig {
+  #[serde(default = "create_default_native_dir")]
+  pub native_dir: String,
   #[serde(default = "create_default_gen_input_crates")]
-  pub gen_input_crates: Vec<String>,
+  pub gen_input_crates: Vec<InputCrate>,
   #[serde(default = "create_default_gen_output_dir")]
   pub gen_output_dir: String,
+  #[serde(default = "create_default_gen_include")]
+  pub gen_include: Vec<String>,
+  #[serde(default)]
+  pub gen_exclude: Vec<String>,
+  #[serde(default)]
+  pub gen_json: bool,
+  #[serde(default)]
+  pub gen_sealed_classes: bool,
+  #[serde(default)]
+  pub namespace: String,
+}
+
+/// A crate that `rinf gen` searches for signals.
+/// This is either a crate name under the native directory,
+/// or an explicit crate path with an optional output directory.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum InputCrate {
+  Name(String),
+  Detailed(InputCrateDetail),
+}
+
+/// Explicit location of a crate that `rinf gen` searches for signals.
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputCrateDetail {
+  /// Crate directory relative to the Flutter project root.
+  pub path: String,
+  /// Output directory for signals of this crate.
+  /// If not provided, `gen_output_dir` is used.
+  pub output_dir: Option<String>,
+}
+
+impl Display for InputCrate {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Name(name) => write!(f, "{name}"),
+      Self::Detailed(detail) => match &detail.output_dir {
+        Some(output_dir) => write!(f, "{} -> {}", detail.path, output_dir),
+        None => write!(f, "{}", detail.path),
+      },
+    }
+  }
 }
 
 impl Display for RinfConfig {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let input_crates: Vec<String> = self
+      .gen_input_crates
+      .iter()
+      .map(|c| c.to_string())
+      .collect();
     write!(
       f,
-      "gen_input_crates: {}\
-      \ngen_output_dir: {}",
-      self.gen_input_crates.join(", "),
+      "native_dir: {}\
+      \ngen_input_crates: {}\
+      \ngen_output_dir: {}\
+      \ngen_include: {}\
+      \ngen_exclude: {}\
+      \ngen_json: {}\
+      \ngen_sealed_classes: {}\
+      \nnamespace: {}",
+      self.native_dir,
+      input_crates.join(", "),
       self.gen_output_dir,
+      self.gen_include.join(", "),
+      self.gen_exclude.join(", "),
+      self.gen_json,
+      self.gen_sealed_classes,
+      self.namespace,
     )
   }
 }
@@ -29,20 +94,127 @@ impl Display for RinfConfig {
 impl Default for RinfConfig {
   fn default() -> Self {
     Self {
+      native_dir: create_default_native_dir(),
       gen_input_crates: create_default_gen_input_crates(),
       gen_output_dir: create_default_gen_output_dir(),
+      gen_include: create_default_gen_include(),
+      gen_exclude: Vec::new(),
+      gen_json: false,
+      gen_sealed_classes: false,
+      namespace: String::new(),
     }
   }
 }
 
-fn create_default_gen_input_crates() -> Vec<String> {
-  vec!["hub".to_owned()]
+fn create_default_native_dir() -> String {
+  "native".to_owned()
+}
+
+fn create_default_gen_input_crates() -> Vec<InputCrate> {
+  vec![InputCrate::Name("hub".to_owned())]
 }
 
 fn create_default_gen_output_dir() -> String {
   "lib/src/bindings".to_owned()
 }
 
+fn create_default_gen_include() -> Vec<String> {
+  vec!["**/*.rs".to_owned()]
+}
+
+impl RinfConfig {
+  /// Returns the directory of a crate under the native directory.
+  pub fn native_crate_dir(&self, root_dir: &Path, name: &str) -> PathBuf {
+    root_dir.join(&self.native_dir).join(name)
+  }
+
+  /// Returns the directory of an input crate.
+  fn input_crate_dir(
+    &self,
+    root_dir: &Path,
+    input_crate: &InputCrate,
+  ) -> PathBuf {
+    match input_crate {
+      InputCrate::Name(name) => self.native_crate_dir(root_dir, name),
+      InputCrate::Detailed(detail) => root_dir.join(&detail.path),
+    }
+  }
+
+  /// Returns the directories of all input crates.
+  pub fn input_crate_dirs(&self, root_dir: &Path) -> Vec<PathBuf> {
+    self
+      .gen_input_crates
+      .iter()
+      .map(|input_crate| self.input_crate_dir(root_dir, input_crate))
+      .collect()
+  }
+
+  /// Groups the directories of input crates by their output directories.
+  pub fn gen_targets(
+    &self,
+    root_dir: &Path,
+  ) -> BTreeMap<PathBuf, Vec<PathBuf>> {
+    let mut gen_targets: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    for input_crate in &self.gen_input_crates {
+      let crate_dir = self.input_crate_dir(root_dir, input_crate);
+      let output_dir = match input_crate {
+        InputCrate::Detailed(InputCrateDetail {
+          output_dir: Some(output_dir),
+          ..
+        }) => output_dir,
+        _ => &self.gen_output_dir,
+      };
+      gen_targets
+        .entry(root_dir.join(output_dir))
+        .or_default()
+        .push(crate_dir);
+    }
+    gen_targets
+  }
+
+  /// Builds the filter for Rust source files
+  /// from the include and exclude glob patterns.
+  pub fn source_filter(&self) -> Result<SourceFilter, SetupError> {
+    Ok(SourceFilter {
+      include: build_glob_set(&self.gen_include, bad_glob_pattern)?,
+      exclude: build_glob_set(&self.gen_exclude, bad_glob_pattern)?,
+    })
+  }
+}
+
+/// Decides which Rust source files are searched for signals.
+/// Paths are matched relative to the `src` directory of each crate.
+pub struct SourceFilter {
+  include: GlobSet,
+  exclude: GlobSet,
+}
+
+impl SourceFilter {
+  pub fn is_included(&self, relative_path: &Path) -> bool {
+    self.include.is_match(relative_path)
+      && !self.exclude.is_match(relative_path)
+  }
+}
+
+fn bad_glob_pattern(pattern: String) -> SetupError {
+  SetupError::PubConfig(format!("Invalid glob pattern `{pattern}`"))
+}
+
+/// Builds a glob set from the patterns.
+/// Invalid patterns are turned into an error
+/// with the given constructor.
+pub fn build_glob_set(
+  patterns: &[String],
+  to_error: fn(String) -> SetupError,
+) -> Result<GlobSet, SetupError> {
+  let mut builder = GlobSetBuilder::new();
+  for pattern in patterns {
+    let glob = Glob::new(pattern).map_err(|_| to_error(pattern.to_owned()))?;
+    builder.add(glob);
+  }
+  builder.build().map_err(|_| to_error(patterns.join(", ")))
+}
+
 /// Attempts to load the rinf configuration from the provided pubspec.yaml file.
 /// If no rinf configuration is found, the default configuration is returned.
 /// If the Rinf configuration is invalid, an exception is thrown.
@@ -62,9 +234,29 @@ pub fn load_verified_rinf_config(
     Some(map) => from_value(map)?,
     None => RinfConfig::default(),
   };
+  verify_namespace(&config.namespace)?;
   Ok(config)
 }
 
+/// Checks that the namespace can be used in exported symbols
+/// and Dart identifiers.
+fn verify_namespace(namespace: &str) -> Result<(), SetupError> {
+  if namespace.is_empty() {
+    return Ok(());
+  }
+  let is_valid = namespace.starts_with(|c: char| c.is_ascii_lowercase())
+    && namespace
+      .chars()
+      .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+  if is_valid {
+    Ok(())
+  } else {
+    Err(SetupError::PubConfig(format!(
+      "Namespace `{namespace}` should be in snake case"
+    )))
+  }
+}
+
 pub fn read_publish_to(file_path: &Path) -> Option<String> {
   let content = std::fs::read_to_string(file_path).ok()?;
   let file_yaml: Value = from_str(&content).ok()?;
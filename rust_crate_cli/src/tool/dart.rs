@@ -0,0 +1,168 @@
+use heck::{ToLowerCamelCase, ToUpperCamelCase};
+use serde_reflection::{ContainerFormat, Format, Named, VariantFormat};
+use std::collections::BTreeMap;
+
+/// Shape of the fields in a generated Dart class,
+/// following the conventions of `serde-generate`.
+pub enum FieldsShape {
+  Unit,
+  NewType(Format),
+  Tuple(Vec<Format>),
+  Struct(Vec<Named<Format>>),
+}
+
+impl FieldsShape {
+  pub fn from_container(container: &ContainerFormat) -> Option<Self> {
+    match container {
+      ContainerFormat::UnitStruct => Some(Self::Unit),
+      ContainerFormat::NewTypeStruct(format) => {
+        Some(Self::NewType(format.as_ref().to_owned()))
+      }
+      ContainerFormat::TupleStruct(formats) => {
+        Some(Self::Tuple(formats.to_owned()))
+      }
+      ContainerFormat::Struct(fields) => Some(Self::Struct(fields.to_owned())),
+      ContainerFormat::Enum(_) => None,
+    }
+  }
+
+  pub fn from_variant(variant: &VariantFormat) -> Self {
+    match variant {
+      VariantFormat::NewType(format) => {
+        Self::NewType(format.as_ref().to_owned())
+      }
+      VariantFormat::Tuple(formats) => Self::Tuple(formats.to_owned()),
+      VariantFormat::Struct(fields) => Self::Struct(fields.to_owned()),
+      VariantFormat::Unit | VariantFormat::Variable(_) => Self::Unit,
+    }
+  }
+}
+
+/// Inserts members right after the opening brace of a class.
+pub fn insert_class_members(code: &str, header: &str, members: &str) -> String {
+  let Some(header_start) = code.find(header) else {
+    return code.to_owned();
+  };
+  let Some(brace_offset) = code[header_start..].find("{\n") else {
+    return code.to_owned();
+  };
+  let insert_at = header_start + brace_offset + 2;
+  let mut new_code = code.to_owned();
+  new_code.insert_str(insert_at, members);
+  new_code
+}
+
+/// Whether `serde-generate` outputs the enum as a Dart enum.
+pub fn is_c_style(variants: &BTreeMap<u32, Named<VariantFormat>>) -> bool {
+  variants
+    .values()
+    .all(|variant| matches!(variant.value, VariantFormat::Unit))
+}
+
+/// Escapes class names that conflict with Dart's core types,
+/// just like `serde-generate` does.
+pub fn quote_class_name(name: &str) -> String {
+  match name {
+    "List" => "List_".to_owned(),
+    "Map" => "Map_".to_owned(),
+    _ => name.to_owned(),
+  }
+}
+
+/// Returns the name of the class that `serde-generate` outputs
+/// for a variant of an enum with data.
+pub fn quote_variant_class_name(name: &str, variant_name: &str) -> String {
+  let class_name = quote_class_name(name).to_upper_camel_case();
+  format!("{class_name}{}", quote_field(variant_name))
+}
+
+/// Escapes names that conflict with Dart's object members,
+/// just like `serde-generate` does.
+pub fn quote_field(name: &str) -> String {
+  match name {
+    "hashCode" => "hashCode_".to_owned(),
+    "runtimeType" => "runtimeType_".to_owned(),
+    _ => name.to_owned(),
+  }
+}
+
+/// Escapes field names in the way `serde-generate` does.
+pub fn quote_field_name(name: &str) -> String {
+  quote_field(&name.to_lower_camel_case())
+}
+
+/// Returns the Dart type that `serde-generate` uses for the format.
+pub fn dart_type(format: &Format) -> String {
+  match format {
+    Format::TypeName(name) => quote_class_name(name),
+    Format::Unit => "Unit".to_owned(),
+    Format::Bool => "bool".to_owned(),
+    Format::I8
+    | Format::I16
+    | Format::I32
+    | Format::I64
+    | Format::U8
+    | Format::U16
+    | Format::U32
+    | Format::Char => "int".to_owned(),
+    Format::U64 => "Uint64".to_owned(),
+    Format::I128 => "Int128".to_owned(),
+    Format::U128 => "Uint128".to_owned(),
+    Format::F32 | Format::F64 => "double".to_owned(),
+    Format::Str => "String".to_owned(),
+    Format::Bytes => "Bytes".to_owned(),
+    Format::Option(inner) => format!("{}?", dart_type(inner)),
+    Format::Seq(inner) | Format::TupleArray { content: inner, .. } => {
+      format!("List<{}>", dart_type(inner))
+    }
+    Format::Map { key, value } => {
+      format!("Map<{}, {}>", dart_type(key), dart_type(value))
+    }
+    Format::Tuple(formats) => {
+      let types: Vec<String> = formats.iter().map(dart_type).collect();
+      format!("Tuple{}<{}>", formats.len(), types.join(", "))
+    }
+    Format::Variable(_) => "dynamic".to_owned(),
+  }
+}
+
+/// Inserts a doc comment above the line that contains the target
+/// inside the block that starts with the header.
+pub fn insert_doc_comment(
+  code: &str,
+  header: &str,
+  target: &str,
+  doc_comment: &str,
+) -> String {
+  let Some(header_start) = code.find(header) else {
+    return code.to_owned();
+  };
+  let block_end = code[header_start..]
+    .find("\n}")
+    .map_or(code.len(), |offset| header_start + offset);
+  // The target should be the first thing on its line.
+  let found = code[header_start..block_end]
+    .match_indices(target)
+    .map(|(offset, _)| header_start + offset)
+    .find_map(|target_start| {
+      let line_start = code[..target_start].rfind('\n').map_or(0, |i| i + 1);
+      let indent = &code[line_start..target_start];
+      indent
+        .chars()
+        .all(|c| c == ' ')
+        .then_some((line_start, indent))
+    });
+  let Some((line_start, indent)) = found else {
+    return code.to_owned();
+  };
+  let doc_lines: String = doc_comment
+    .lines()
+    .map(|line| match line {
+      "" => format!("{indent}///\n"),
+      _ => format!("{indent}/// {line}\n"),
+    })
+    .collect();
+  let mut new_code = code.to_owned();
+  new_code.insert_str(line_start, &doc_lines);
+  new_code
+}
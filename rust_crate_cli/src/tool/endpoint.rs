@@ -0,0 +1,145 @@
+use crate::tool::{GenTarget, SetupError, SignalAttribute, Traced};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{read_to_string, write};
+use std::path::Path;
+
+/// Name of the file in each input crate that lists endpoint IDs.
+/// Derive macros of the Rinf crate read this file at compile time.
+pub static ENDPOINT_TABLE_FILE: &str = "rinf_endpoints.txt";
+
+/// Endpoint IDs below this number are reserved for Rinf itself.
+pub static FIRST_SIGNAL_ENDPOINT_ID: u32 = 16;
+
+/// Endpoints that are handled by Rinf itself, with their IDs.
+pub static RESERVED_ENDPOINTS: [(&str, u32); 5] = [
+  ("RinfOut", 0),
+  ("RinfLog", 1),
+  ("RinfPanic", 2),
+  ("RinfBatch", 3),
+  ("RinfLifecycle", 4),
+];
+
+static ENDPOINT_TABLE_HEADER: &str = "\
+# Endpoint IDs of signals, assigned by `rinf gen`.
+# Do not edit this file by hand.
+";
+
+/// Whether the signal is sent across the boundary,
+/// which means that it needs an endpoint ID.
+fn is_endpoint(extracted_attrs: &BTreeSet<SignalAttribute>) -> bool {
+  extracted_attrs
+    .iter()
+    .any(|attr| !matches!(attr, SignalAttribute::SignalPiece))
+}
+
+/// Reads the endpoint IDs written in the table file of a crate.
+/// Malformed lines are ignored.
+fn read_endpoint_table(crate_dir: &Path) -> BTreeMap<String, u32> {
+  let table_file = crate_dir.join(ENDPOINT_TABLE_FILE);
+  let Ok(content) = read_to_string(table_file) else {
+    return BTreeMap::new();
+  };
+  content
+    .lines()
+    .filter(|line| !line.starts_with('#'))
+    .filter_map(|line| {
+      let (name, id) = line.split_once('=')?;
+      Some((name.trim().to_owned(), id.trim().parse().ok()?))
+    })
+    .collect()
+}
+
+/// Reads the endpoint IDs that `rinf gen` assigned before,
+/// including the endpoints reserved by Rinf.
+pub fn read_endpoint_ids(gen_targets: &[GenTarget]) -> BTreeMap<String, u32> {
+  let mut endpoint_ids: BTreeMap<String, u32> = gen_targets
+    .iter()
+    .flat_map(|gen_target| &gen_target.crate_dirs)
+    .flat_map(|crate_dir| read_endpoint_table(crate_dir))
+    .collect();
+  for (name, id) in RESERVED_ENDPOINTS {
+    endpoint_ids.insert(name.to_owned(), id);
+  }
+  endpoint_ids
+}
+
+/// Assigns a small endpoint ID to each signal.
+/// IDs from the existing table files are kept,
+/// so that they stay stable as signals are added or removed.
+/// All signals share one ID space
+/// because they can be linked into the same Rust library.
+pub fn assign_endpoint_ids(gen_targets: &[GenTarget]) -> BTreeMap<String, u32> {
+  let existing_ids = read_endpoint_ids(gen_targets);
+  let classes: BTreeSet<&String> = gen_targets
+    .iter()
+    .flat_map(|gen_target| &gen_target.traced.signal_attrs)
+    .filter(|(_, extracted_attrs)| is_endpoint(extracted_attrs))
+    .map(|(class, _)| class)
+    .collect();
+
+  let mut endpoint_ids = BTreeMap::new();
+  let mut used_ids = BTreeSet::new();
+  let mut unassigned = Vec::new();
+  for class in classes {
+    match existing_ids.get(class) {
+      Some(&id) if id >= FIRST_SIGNAL_ENDPOINT_ID && used_ids.insert(id) => {
+        endpoint_ids.insert(class.to_owned(), id);
+      }
+      _ => unassigned.push(class),
+    }
+  }
+  let mut free_ids =
+    (FIRST_SIGNAL_ENDPOINT_ID..).filter(|id| !used_ids.contains(id));
+  for (class, id) in unassigned.into_iter().zip(&mut free_ids) {
+    endpoint_ids.insert(class.to_owned(), id);
+  }
+  endpoint_ids
+}
+
+/// Writes the endpoint IDs of the signals in each input crate
+/// to the table file of that crate.
+/// Files are only written when their content changes,
+/// so that Cargo doesn't rebuild the crates without a reason.
+pub fn write_endpoint_tables(
+  gen_targets: &[GenTarget],
+  endpoint_ids: &BTreeMap<String, u32>,
+) -> Result<(), SetupError> {
+  for gen_target in gen_targets {
+    for crate_dir in &gen_target.crate_dirs {
+      let classes = get_crate_classes(&gen_target.traced, crate_dir);
+      let table_file = crate_dir.join(ENDPOINT_TABLE_FILE);
+      if classes.is_empty() && !table_file.exists() {
+        continue;
+      }
+      let mut entries: Vec<(u32, &str)> = classes
+        .into_iter()
+        .filter_map(|class| Some((*endpoint_ids.get(class)?, class)))
+        .collect();
+      entries.sort();
+      let mut content = ENDPOINT_TABLE_HEADER.to_owned();
+      for (id, class) in entries {
+        content.push_str(&format!("{class} = {id}\n"));
+      }
+      if read_to_string(&table_file).ok().as_ref() != Some(&content) {
+        write(&table_file, content)?;
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Returns the names of endpoint signals defined in the crate.
+fn get_crate_classes<'a>(traced: &'a Traced, crate_dir: &Path) -> Vec<&'a str> {
+  traced
+    .signal_attrs
+    .iter()
+    .filter(|(_, extracted_attrs)| is_endpoint(extracted_attrs))
+    .filter(|(class, _)| {
+      traced
+        .locations
+        .get(*class)
+        .is_some_and(|location| location.file.starts_with(crate_dir))
+    })
+    .map(|(class, _)| class.as_str())
+    .collect()
+}
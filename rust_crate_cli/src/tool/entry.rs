@@ -1,18 +1,23 @@
 use crate::dimmedln;
 use crate::tool::{
-  SetupError, apply_rust_template, build_webassembly,
-  check_internet_connection, generate_dart_code, load_verified_rinf_config,
-  provide_server_command, read_publish_to, watch_and_generate_dart_code,
+  MonitorFilter, SetupError, SignalDirection, apply_rust_template,
+  build_webassembly, check_internet_connection, generate_dart_code,
+  inspect_signals, load_verified_rinf_config, monitor_signals,
+  provide_server_command, read_publish_to, report_data, report_success,
+  serve_web_directory, set_json_output, watch_and_generate_dart_code,
 };
 use clap::{Parser, Subcommand};
 use std::env::current_dir;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(about = "CLI tool for building apps using Rust in Flutter")]
 struct CliInput {
   #[command(subcommand)]
   command: CliCommand,
+  /// Print machine-readable events as JSON lines
+  #[arg(long, global = true)]
+  json: bool,
 }
 
 #[derive(Subcommand)]
@@ -26,6 +31,24 @@ enum CliCommand {
     /// Continuously watch Rust files
     #[arg(short, long)]
     watch: bool,
+    /// Also write a schema document of all signals to this file,
+    /// as YAML for `.yaml` or `.yml` files and as JSON otherwise
+    #[arg(long, value_name = "FILE")]
+    schema: Option<PathBuf>,
+  },
+  /// List all signals found in Rust files with their schemas
+  Inspect,
+  /// Show signals of the app running in debug mode as they cross the bridge
+  Monitor {
+    /// Address of the signal inspector in the app
+    #[arg(short, long, default_value = "127.0.0.1:9339")]
+    address: String,
+    /// Only show signals whose names match these glob patterns
+    #[arg(short, long, value_name = "PATTERN")]
+    filter: Vec<String>,
+    /// Only show signals going in this direction
+    #[arg(short, long, value_enum)]
+    direction: Option<SignalDirection>,
   },
   /// Build the WebAssembly module for the web
   Wasm {
@@ -38,10 +61,26 @@ enum CliCommand {
     /// Include the release mode argument
     #[arg(short, long)]
     release: bool,
+    /// Serve a built web directory instead, `build/web` by default
+    #[arg(
+      short,
+      long,
+      value_name = "DIR",
+      num_args = 0..=1,
+      default_missing_value = "build/web"
+    )]
+    serve: Option<PathBuf>,
+    /// Port of the local web server
+    #[arg(short, long, default_value_t = 8080)]
+    port: u16,
   },
 }
 
 pub fn run_command() -> Result<(), SetupError> {
+  // Parse user input first to know the output mode.
+  let cli = CliInput::parse();
+  set_json_output(cli.json);
+
   // Check the internet connection status and remember it.
   let is_internet_connected = check_internet_connection();
 
@@ -52,39 +91,69 @@ pub fn run_command() -> Result<(), SetupError> {
   }
 
   // Run a command from user input.
-  let cli = CliInput::parse();
   match cli.command {
     CliCommand::Config => {
       let rinf_config = load_verified_rinf_config(&root_dir)?;
-      dimmedln!("{}", rinf_config);
+      report_data("config", &rinf_config, &rinf_config.to_string());
     }
     CliCommand::Template => {
       let rinf_config = load_verified_rinf_config(&root_dir)?;
       apply_rust_template(&root_dir, &rinf_config)?;
-      println!("Rust template is now ready 🎉");
+      report_success("Rust template is now ready 🎉");
     }
-    CliCommand::Gen { watch } => {
+    CliCommand::Gen { watch, schema } => {
       let rinf_config = load_verified_rinf_config(&root_dir)?;
+      let schema_file = schema.map(|path| root_dir.join(path));
       if watch {
-        watch_and_generate_dart_code(&root_dir, &rinf_config)?;
+        watch_and_generate_dart_code(
+          &root_dir,
+          &rinf_config,
+          schema_file.as_deref(),
+        )?;
       } else {
-        generate_dart_code(&root_dir, &rinf_config)?;
-        println!("Dart signal classes were generated successfully 🎉");
+        generate_dart_code(&root_dir, &rinf_config, schema_file.as_deref())?;
+        report_success("Dart signal classes were generated successfully 🎉");
       }
     }
+    CliCommand::Inspect => {
+      let rinf_config = load_verified_rinf_config(&root_dir)?;
+      inspect_signals(&root_dir, &rinf_config)?;
+    }
+    CliCommand::Monitor {
+      address,
+      filter,
+      direction,
+    } => {
+      let rinf_config = load_verified_rinf_config(&root_dir)?;
+      let monitor_filter = MonitorFilter {
+        patterns: filter,
+        direction,
+      };
+      monitor_signals(&root_dir, &rinf_config, &address, &monitor_filter)?;
+    }
     CliCommand::Wasm { release } => {
-      build_webassembly(&root_dir, release, is_internet_connected)?;
+      let rinf_config = load_verified_rinf_config(&root_dir)?;
+      build_webassembly(
+        &root_dir,
+        &rinf_config,
+        release,
+        is_internet_connected,
+      )?;
       dimmedln!("To get the Flutter web server command, run `rinf server`");
-      println!("The WebAssembly module has been built to `web/pkg` 🎉");
-    }
-    CliCommand::Server { release } => {
-      provide_server_command(release)?;
-      let full_guide = concat!(
-        "Full `flutter run` command for the web",
-        " has been copied to the clipboard"
-      );
-      dimmedln!("{}", full_guide);
+      report_success("The WebAssembly module has been built to `web/pkg` 🎉");
     }
+    CliCommand::Server {
+      release,
+      serve,
+      port,
+    } => match serve {
+      Some(serve_dir) => {
+        serve_web_directory(&root_dir.join(serve_dir), port)?;
+      }
+      None => {
+        provide_server_command(release)?;
+      }
+    },
   }
 
   Ok(())
@@ -7,9 +7,11 @@ use std::path::PathBuf;
 pub enum SetupError {
   // Below are automatically converted variants.
   Io(std::io::Error),
-  Yaml(serde_yml::Error),
+  Yaml(serde_norway::Error),
+  Json(serde_json::Error),
   Clipboard(arboard::Error),
   WatchingFile(notify::Error),
+  WebSocket(tungstenite::Error),
   // Below are manually constructed variants.
   ReflectionModule,
   PubConfig(String),
@@ -19,6 +21,7 @@ pub enum SetupError {
   DuplicatedSignal(String),
   CodeSyntax(String),
   SubprocessError,
+  BadFilter(String),
 }
 
 impl Error for SetupError {
@@ -26,13 +29,61 @@ impl Error for SetupError {
     match self {
       Self::Io(e) => Some(e),
       Self::Yaml(e) => Some(e),
+      Self::Json(e) => Some(e),
       Self::Clipboard(e) => Some(e),
       Self::WatchingFile(e) => Some(e),
+      Self::WebSocket(e) => Some(e),
       _ => None,
     }
   }
 }
 
+impl SetupError {
+  /// Returns a stable identifier of the error class
+  /// for machine-readable output.
+  pub fn code(&self) -> &'static str {
+    match self {
+      Self::Io(_) => "io",
+      Self::Yaml(_) => "yaml",
+      Self::Json(_) => "json",
+      Self::Clipboard(_) => "clipboard",
+      Self::WatchingFile(_) => "watching_file",
+      Self::ReflectionModule => "reflection_module",
+      Self::PubConfig(_) => "pub_config",
+      Self::BadFilePath(_) => "bad_file_path",
+      Self::NotFlutterApp => "not_flutter_app",
+      Self::TemplateApplied => "template_applied",
+      Self::DuplicatedSignal(_) => "duplicated_signal",
+      Self::CodeSyntax(_) => "code_syntax",
+      Self::SubprocessError => "subprocess_error",
+      Self::WebSocket(_) => "web_socket",
+      Self::BadFilter(_) => "bad_filter",
+    }
+  }
+
+  /// Returns the process exit code of the error class.
+  /// Codes 1 and 2 are left for panics and invalid CLI arguments.
+  pub fn exit_code(&self) -> u8 {
+    match self {
+      Self::Io(_) => 10,
+      Self::Yaml(_) => 11,
+      Self::Json(_) => 12,
+      Self::Clipboard(_) => 13,
+      Self::WatchingFile(_) => 14,
+      Self::ReflectionModule => 15,
+      Self::PubConfig(_) => 16,
+      Self::BadFilePath(_) => 17,
+      Self::NotFlutterApp => 18,
+      Self::TemplateApplied => 19,
+      Self::DuplicatedSignal(_) => 20,
+      Self::CodeSyntax(_) => 21,
+      Self::SubprocessError => 22,
+      Self::WebSocket(_) => 23,
+      Self::BadFilter(_) => 24,
+    }
+  }
+}
+
 impl Display for SetupError {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     match self {
@@ -42,6 +93,9 @@ impl Display for SetupError {
       Self::Yaml(e) => {
         write!(f, "Failed to parse YAML: {e}")
       }
+      Self::Json(e) => {
+        write!(f, "Failed to handle JSON: {e}")
+      }
       Self::Clipboard(e) => {
         write!(f, "Failed to use clipboard: {e}")
       }
@@ -72,6 +126,12 @@ impl Display for SetupError {
       Self::SubprocessError => {
         write!(f, "A subprocess did not exit successfully")
       }
+      Self::WebSocket(e) => {
+        write!(f, "Failed to communicate with the app: {e}")
+      }
+      Self::BadFilter(p) => {
+        write!(f, "Invalid signal filter `{p}`")
+      }
     }
   }
 }
@@ -82,12 +142,18 @@ impl From<std::io::Error> for SetupError {
   }
 }
 
-impl From<serde_yml::Error> for SetupError {
-  fn from(err: serde_yml::Error) -> Self {
+impl From<serde_norway::Error> for SetupError {
+  fn from(err: serde_norway::Error) -> Self {
     Self::Yaml(err)
   }
 }
 
+impl From<serde_json::Error> for SetupError {
+  fn from(err: serde_json::Error) -> Self {
+    Self::Json(err)
+  }
+}
+
 impl From<arboard::Error> for SetupError {
   fn from(err: arboard::Error) -> Self {
     Self::Clipboard(err)
@@ -99,3 +165,9 @@ impl From<notify::Error> for SetupError {
     Self::WatchingFile(err)
   }
 }
+
+impl From<tungstenite::Error> for SetupError {
+  fn from(err: tungstenite::Error) -> Self {
+    Self::WebSocket(err)
+  }
+}
@@ -1,7 +1,13 @@
 use crate::dimmedln;
-use crate::tool::{CleanFileName, RinfConfig, SetupError};
+use crate::tool::{
+  CleanFileName, RinfConfig, SetupError, SourceFilter, assign_endpoint_ids,
+  dart_type, generate_json_code, generate_sealed_code, insert_doc_comment,
+  is_c_style, quote_class_name, quote_field_name, quote_variant_class_name,
+  report_generated, report_warning, write_endpoint_tables, write_signal_schema,
+};
 use heck::{ToLowerCamelCase, ToSnakeCase};
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
 use serde_generate::dart::{CodeGenerator, Installer};
 use serde_generate::{CodeGeneratorConfig, Encoding, SourceInstaller};
 use serde_reflection::{ContainerFormat, Format, Named, VariantFormat};
@@ -9,7 +15,7 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{
   create_dir_all, read_dir, read_to_string, remove_dir_all, rename, write,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::channel;
 use std::time::Duration;
 use syn::spanned::Spanned;
@@ -20,8 +26,8 @@ use syn::{
 
 static GEN_MOD: &str = "signals";
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-enum SignalAttribute {
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Serialize)]
+pub enum SignalAttribute {
   SignalPiece,
   DartSignal,
   DartSignalBinary,
@@ -53,7 +59,7 @@ fn extract_signal_attributes(
           "SignalPiece" => Some(SignalAttribute::SignalPiece),
           "DartSignal" => Some(SignalAttribute::DartSignal),
           "DartSignalBinary" => Some(SignalAttribute::DartSignalBinary),
-          "RustSignal" => Some(SignalAttribute::RustSignalBinary),
+          "RustSignal" => Some(SignalAttribute::RustSignal),
           "RustSignalBinary" => Some(SignalAttribute::RustSignalBinary),
           _ => None,
         };
@@ -87,6 +93,27 @@ fn extract_doc_comment(attrs: &[Attribute]) -> String {
   lines.join("\n")
 }
 
+/// Records doc comments of named fields
+/// at paths under the struct or the enum variant.
+fn extract_field_doc_comments(
+  traced: &mut Traced,
+  parent_path: &[String],
+  fields: &syn::Fields,
+) {
+  for field in fields.iter().filter(is_exposed) {
+    let Some(ident) = &field.ident else {
+      continue;
+    };
+    let doc_comment = extract_doc_comment(&field.attrs);
+    if doc_comment.is_empty() {
+      continue;
+    }
+    let mut field_path = parent_path.to_vec();
+    field_path.push(ident.to_string());
+    traced.doc_comments.insert(field_path, doc_comment);
+  }
+}
+
 /// Convert a `syn` field type to a `serde_reflection::Format`.
 /// This function handles common primitives
 /// and container types like `Option` and `Vec`.
@@ -360,14 +387,15 @@ fn check_signal_name(name: &str, traced: &Traced) -> Result<(), SetupError> {
 fn process_items_in_module(
   items: &[Item],
   traced: &mut Traced,
-  file_name: &str,
+  file_path: &Path,
 ) -> Result<(), SetupError> {
+  let file_name = file_path.clean_file_name()?;
   for item in items {
     match item {
       Item::Mod(m) if m.content.is_some() => {
         // Recursively process items in nested modules.
         if let Some(inner_items) = m.content.as_ref().map(|p| &p.1) {
-          process_items_in_module(inner_items, traced, file_name)?;
+          process_items_in_module(inner_items, traced, file_path)?;
         }
       }
       Item::Struct(s) => {
@@ -378,8 +406,14 @@ fn process_items_in_module(
           check_signal_name(&item_name, traced)?;
           trace_struct(traced, s);
           traced.signal_attrs.insert(item_name.clone(), signal_attrs);
+          let location = SourceLocation {
+            file: file_path.to_owned(),
+            line: s.ident.span().start().line,
+          };
+          traced.locations.insert(item_name.clone(), location);
           let doc_comment = extract_doc_comment(&s.attrs);
           let item_path = vec![GEN_MOD.to_owned(), item_name];
+          extract_field_doc_comments(traced, &item_path, &s.fields);
           traced.doc_comments.insert(item_path, doc_comment);
         }
       }
@@ -391,8 +425,24 @@ fn process_items_in_module(
           check_signal_name(&item_name, traced)?;
           trace_enum(traced, e);
           traced.signal_attrs.insert(item_name.clone(), signal_attrs);
+          let location = SourceLocation {
+            file: file_path.to_owned(),
+            line: e.ident.span().start().line,
+          };
+          traced.locations.insert(item_name.clone(), location);
           let doc_comment = extract_doc_comment(&e.attrs);
           let item_path = vec![GEN_MOD.to_owned(), item_name];
+          for variant in e.variants.iter().filter(is_exposed) {
+            let mut variant_path = item_path.clone();
+            variant_path.push(variant.ident.to_string());
+            extract_field_doc_comments(traced, &variant_path, &variant.fields);
+            let variant_doc_comment = extract_doc_comment(&variant.attrs);
+            if !variant_doc_comment.is_empty() {
+              traced
+                .doc_comments
+                .insert(variant_path, variant_doc_comment);
+            }
+          }
           traced.doc_comments.insert(item_path, doc_comment);
         }
       }
@@ -402,26 +452,45 @@ fn process_items_in_module(
   Ok(())
 }
 
-struct Traced {
-  registry: BTreeMap<String, ContainerFormat>,
-  signal_attrs: BTreeMap<String, BTreeSet<SignalAttribute>>,
-  doc_comments: BTreeMap<Vec<String>, String>,
+/// Information about signals collected from Rust source files.
+pub struct Traced {
+  pub registry: BTreeMap<String, ContainerFormat>,
+  pub signal_attrs: BTreeMap<String, BTreeSet<SignalAttribute>>,
+  /// Doc comments at paths of types, fields, enum variants,
+  /// and fields of enum variants, each starting with the module name.
+  pub doc_comments: BTreeMap<Vec<String>, String>,
+  pub locations: BTreeMap<String, SourceLocation>,
+}
+
+/// Where a signal type is defined in Rust source files.
+pub struct SourceLocation {
+  pub file: PathBuf,
+  pub line: usize,
 }
 
-fn visit_rust_files(dir: &Path, traced: &mut Traced) -> Result<(), SetupError> {
+fn visit_rust_files(
+  dir: &Path,
+  source_dir: &Path,
+  source_filter: &SourceFilter,
+  traced: &mut Traced,
+) -> Result<(), SetupError> {
   let entries = read_dir(dir)?;
   for entry in entries.filter_map(Result::ok) {
     let entry_path = entry.path();
+    let relative_path =
+      entry_path.strip_prefix(source_dir).unwrap_or(&entry_path);
     if entry_path.is_dir() {
       // Recurse into subdirectory.
-      visit_rust_files(&entry_path, traced)?;
-    } else if entry_path.extension().is_some_and(|ext| ext == "rs") {
+      visit_rust_files(&entry_path, source_dir, source_filter, traced)?;
+    } else if entry_path.extension().is_some_and(|ext| ext == "rs")
+      && source_filter.is_included(relative_path)
+    {
       // This is a Rust file, so process it.
       let file_name = entry_path.clean_file_name()?;
       let content = read_to_string(&entry_path)?;
       let syntax_tree: File = syn::parse_file(&content)
         .map_err(|_| SetupError::CodeSyntax(file_name.to_owned()))?;
-      process_items_in_module(&syntax_tree.items, traced, &file_name)?;
+      process_items_in_module(&syntax_tree.items, traced, &entry_path)?;
     }
   }
   Ok(())
@@ -430,10 +499,16 @@ fn visit_rust_files(dir: &Path, traced: &mut Traced) -> Result<(), SetupError> {
 fn generate_class_extension_code(
   gen_dir: &Path,
   class: &str,
+  endpoint_id: u32,
   extracted_attrs: &BTreeSet<SignalAttribute>,
+  rinf_config: &RinfConfig,
 ) -> Result<(), SetupError> {
-  let snake_class = class.to_snake_case();
-  let class_file = gen_dir.join(GEN_MOD).join(format!("{snake_class}.dart"));
+  let namespace_arg = if rinf_config.namespace.is_empty() {
+    String::new()
+  } else {
+    format!("\n      namespace: '{}',", rinf_config.namespace)
+  };
+  let class_file = get_dart_class_file(gen_dir, class);
   let mut code = read_to_string(&class_file)?;
 
   if extracted_attrs.contains(&SignalAttribute::DartSignalBinary) {
@@ -446,9 +521,9 @@ extension {class}DartSignalExt on {class} {{
   void sendSignalToRust(Uint8List binary) {{
     final messageBytes = bincodeSerialize();
     sendDartSignal(
-      'rinf_send_dart_signal_{snake_class}',
+      {endpoint_id},
       messageBytes,
-      binary,
+      binary,{namespace_arg}
     );
   }}
 }}
@@ -466,9 +541,9 @@ extension {class}DartSignalExt on {class} {{
     final messageBytes = bincodeSerialize();
     final binary = Uint8List(0);
     sendDartSignal(
-      'rinf_send_dart_signal_{snake_class}',
+      {endpoint_id},
       messageBytes,
-      binary,
+      binary,{namespace_arg}
     );
   }}
 }}
@@ -486,8 +561,7 @@ fn generate_class_interface_code(
   class: &str,
   extracted_attrs: &BTreeSet<SignalAttribute>,
 ) -> Result<(), SetupError> {
-  let snake_class = class.to_snake_case();
-  let class_file = gen_dir.join(GEN_MOD).join(format!("{snake_class}.dart"));
+  let class_file = get_dart_class_file(gen_dir, class);
   let mut code = read_to_string(&class_file)?;
 
   let has_rust_signal = extracted_attrs.contains(&SignalAttribute::RustSignal)
@@ -529,6 +603,7 @@ final _{camel_class}StreamController =
 fn generate_shared_code(
   gen_dir: &Path,
   signal_attrs: &BTreeMap<String, BTreeSet<SignalAttribute>>,
+  endpoint_ids: &BTreeMap<String, u32>,
 ) -> Result<(), SetupError> {
   // Write type aliases.
   let mut code = format!("part of '{GEN_MOD}.dart';\n");
@@ -536,7 +611,7 @@ fn generate_shared_code(
   // Write signal handler.
   code.push_str(
     "\nfinal assignRustSignal = \
-        <String, void Function(Uint8List, Uint8List)>{",
+        <int, void Function(Uint8List, Uint8List)>{",
   );
   for (class, extracted_attrs) in signal_attrs {
     let has_rust_signal = extracted_attrs
@@ -545,10 +620,14 @@ fn generate_shared_code(
     if !has_rust_signal {
       continue;
     }
+    let Some(endpoint_id) = endpoint_ids.get(class) else {
+      continue;
+    };
     let camel_class = class.to_lower_camel_case();
     let new_code = format!(
       r#"
-  '{class}': (Uint8List messageBytes, Uint8List binary) {{
+  // {class}
+  {endpoint_id}: (Uint8List messageBytes, Uint8List binary) {{
     final message = {class}.bincodeDeserialize(messageBytes);
     final rustSignal = RustSignalPack(
       message,
@@ -571,10 +650,20 @@ fn generate_shared_code(
 fn generate_interface_code(
   gen_dir: &Path,
   signal_attrs: &BTreeMap<String, BTreeSet<SignalAttribute>>,
+  endpoint_ids: &BTreeMap<String, u32>,
+  rinf_config: &RinfConfig,
 ) -> Result<(), SetupError> {
   // Generate FFI interface code.
   for (class, extracted_attrs) in signal_attrs {
-    generate_class_extension_code(gen_dir, class, extracted_attrs)?;
+    if let Some(&endpoint_id) = endpoint_ids.get(class) {
+      generate_class_extension_code(
+        gen_dir,
+        class,
+        endpoint_id,
+        extracted_attrs,
+        rinf_config,
+      )?;
+    }
     generate_class_interface_code(gen_dir, class, extracted_attrs)?;
   }
 
@@ -593,27 +682,88 @@ export '../serde/serde.dart';"#,
   write(&top_file, top_content)?;
 
   // Write the shared code.
-  generate_shared_code(gen_dir, signal_attrs)?;
+  generate_shared_code(gen_dir, signal_attrs, endpoint_ids)?;
   Ok(())
 }
 
+/// Signals traced from the input crates that share an output directory.
+pub struct GenTarget {
+  pub gen_dir: PathBuf,
+  pub crate_dirs: Vec<PathBuf>,
+  pub traced: Traced,
+}
+
+/// Analyzes the input Rust files and collects type registries
+/// for each output directory.
+pub fn trace_rust_signals(
+  root_dir: &Path,
+  rinf_config: &RinfConfig,
+) -> Result<Vec<GenTarget>, SetupError> {
+  let source_filter = rinf_config.source_filter()?;
+  let mut gen_targets = Vec::new();
+  for (gen_dir, crate_dirs) in rinf_config.gen_targets(root_dir) {
+    let mut traced = Traced {
+      registry: BTreeMap::new(),
+      signal_attrs: BTreeMap::new(),
+      doc_comments: BTreeMap::new(),
+      locations: BTreeMap::new(),
+    };
+    for crate_dir in &crate_dirs {
+      let source_dir = crate_dir.join("src");
+      visit_rust_files(&source_dir, &source_dir, &source_filter, &mut traced)?;
+    }
+    gen_targets.push(GenTarget {
+      gen_dir,
+      crate_dirs,
+      traced,
+    });
+  }
+  Ok(gen_targets)
+}
+
+/// Returns the path of the Dart file generated for the signal type.
+pub fn get_dart_class_file(gen_dir: &Path, class: &str) -> PathBuf {
+  let snake_class = class.to_snake_case();
+  gen_dir.join(GEN_MOD).join(format!("{snake_class}.dart"))
+}
+
 pub fn generate_dart_code(
   root_dir: &Path,
   rinf_config: &RinfConfig,
+  schema_file: Option<&Path>,
 ) -> Result<(), SetupError> {
   // Analyze the input Rust files and collect type registries.
-  let mut traced = Traced {
-    registry: BTreeMap::new(),
-    signal_attrs: BTreeMap::new(),
-    doc_comments: BTreeMap::new(),
-  };
-  for crate_name in &rinf_config.gen_input_crates {
-    let source_dir = root_dir.join("native").join(crate_name).join("src");
-    visit_rust_files(&source_dir, &mut traced)?;
+  let gen_targets = trace_rust_signals(root_dir, rinf_config)?;
+
+  // Assign endpoint IDs and write them for the derive macros.
+  let endpoint_ids = assign_endpoint_ids(&gen_targets);
+  write_endpoint_tables(&gen_targets, &endpoint_ids)?;
+
+  // Write the schema document if requested.
+  if let Some(schema_file) = schema_file {
+    write_signal_schema(root_dir, schema_file, &gen_targets)?;
   }
 
+  // Generate Dart code in each output directory.
+  for gen_target in gen_targets {
+    generate_dart_code_in(
+      &gen_target.gen_dir,
+      gen_target.traced,
+      &endpoint_ids,
+      rinf_config,
+    )?;
+  }
+  Ok(())
+}
+
+fn generate_dart_code_in(
+  gen_dir: &Path,
+  traced: Traced,
+  endpoint_ids: &BTreeMap<String, u32>,
+  rinf_config: &RinfConfig,
+) -> Result<(), SetupError> {
   // Empty the generation folder.
-  let gen_dir = root_dir.join(rinf_config.gen_output_dir.clone());
+  let gen_dir = gen_dir.to_owned();
   let _ = remove_dir_all(&gen_dir);
   create_dir_all(&gen_dir)?;
 
@@ -622,7 +772,7 @@ pub fn generate_dart_code(
     .with_encodings([Encoding::Bincode])
     .with_package_manifest(false)
     .with_c_style_enums(true)
-    .with_comments(traced.doc_comments);
+    .with_comments(get_class_doc_comments(&traced));
 
   // Install serialization modules.
   let installer = Installer::new(gen_dir.clone());
@@ -653,14 +803,126 @@ pub fn generate_dart_code(
   )?;
 
   // Generate Dart interface code for FFI.
-  generate_interface_code(&gen_dir, &traced.signal_attrs)?;
+  generate_interface_code(
+    &gen_dir,
+    &traced.signal_attrs,
+    endpoint_ids,
+    rinf_config,
+  )?;
+
+  // Write doc comments of fields and enum values.
+  generate_member_doc_code(&gen_dir, &traced)?;
+
+  // Generate JSON conversion code if enabled.
+  if rinf_config.gen_json {
+    generate_json_code(&gen_dir, GEN_MOD, &traced.registry)?;
+  }
+
+  // Turn enums into sealed class hierarchies if enabled.
+  if rinf_config.gen_sealed_classes {
+    generate_sealed_code(&gen_dir, &traced.registry)?;
+  }
+
+  // Notify the generated files.
+  report_generated_files(&gen_dir)?;
+  Ok(())
+}
+
+/// Selects doc comments that `serde-generate` writes by itself,
+/// which are those of types and of enum variant classes.
+/// Variant classes are named by joining the enum and variant names.
+fn get_class_doc_comments(traced: &Traced) -> BTreeMap<Vec<String>, String> {
+  let mut class_doc_comments = BTreeMap::new();
+  for (item_path, doc_comment) in &traced.doc_comments {
+    match item_path.as_slice() {
+      [_, _] => {
+        class_doc_comments.insert(item_path.to_owned(), doc_comment.to_owned());
+      }
+      [module, name, variant] => {
+        let Some(ContainerFormat::Enum(variants)) = traced.registry.get(name)
+        else {
+          continue;
+        };
+        if !is_c_style(variants) {
+          let class_path = vec![
+            module.to_owned(),
+            name.to_owned(),
+            format!("{name}{variant}"),
+          ];
+          class_doc_comments.insert(class_path, doc_comment.to_owned());
+        }
+      }
+      _ => {}
+    }
+  }
+  class_doc_comments
+}
+
+/// Writes doc comments that `serde-generate` doesn't handle,
+/// which are those of fields and of Dart enum values.
+fn generate_member_doc_code(
+  gen_dir: &Path,
+  traced: &Traced,
+) -> Result<(), SetupError> {
+  for (item_path, doc_comment) in &traced.doc_comments {
+    let (name, members) = match item_path.as_slice() {
+      [_, name, members @ ..] if !members.is_empty() => (name, members),
+      _ => continue,
+    };
+    let Some(container) = traced.registry.get(name) else {
+      continue;
+    };
+    let class_name = quote_class_name(name);
+    let class_file = get_dart_class_file(gen_dir, name);
+    let mut code = read_to_string(&class_file)?;
+    match (container, members) {
+      (ContainerFormat::Struct(fields), [field_name]) => {
+        if let Some(field) = fields.iter().find(|f| &f.name == field_name) {
+          let header = format!("class {class_name} ");
+          let target = get_field_declaration(field);
+          code = insert_doc_comment(&code, &header, &target, doc_comment);
+        }
+      }
+      (ContainerFormat::Enum(variants), [variant_name])
+        if is_c_style(variants) =>
+      {
+        let header = format!("enum {class_name} ");
+        let target = format!("{},", quote_field_name(variant_name));
+        code = insert_doc_comment(&code, &header, &target, doc_comment);
+      }
+      (ContainerFormat::Enum(variants), [variant_name, field_name]) => {
+        let variant_format = variants
+          .values()
+          .find(|v| &v.name == variant_name)
+          .map(|v| &v.value);
+        if let Some(VariantFormat::Struct(fields)) = variant_format
+          && let Some(field) = fields.iter().find(|f| &f.name == field_name)
+        {
+          let item_name = quote_variant_class_name(name, variant_name);
+          let header = format!("class {item_name} ");
+          let target = get_field_declaration(field);
+          code = insert_doc_comment(&code, &header, &target, doc_comment);
+        }
+      }
+      _ => {}
+    }
+    write(&class_file, code)?;
+  }
   Ok(())
 }
 
+/// Returns the field declaration that `serde-generate` writes.
+fn get_field_declaration(field: &Named<Format>) -> String {
+  let field_type = dart_type(&field.value);
+  let field_name = quote_field_name(&field.name);
+  format!("final {field_type} {field_name};")
+}
+
 /// Watches the Rust source directory for changes and regenerates Dart code.
 pub fn watch_and_generate_dart_code(
   root_dir: &Path,
   rinf_config: &RinfConfig,
+  schema_file: Option<&Path>,
 ) -> Result<(), SetupError> {
   // Create a channel to pass file change events.
   let (sender, receiver) = channel();
@@ -673,20 +935,19 @@ pub fn watch_and_generate_dart_code(
       let event = match event_result {
         Ok(inner) => inner,
         Err(err) => {
-          eprintln!("Watch error: {err}");
+          report_warning(&format!("Watch error: {err}"));
           return;
         }
       };
       let send_result = sender.send(event);
       if let Err(err) = send_result {
-        eprintln!("{err}");
+        report_warning(&err.to_string());
       }
     },
     Config::default(),
   )?;
-  for crate_name in &rinf_config.gen_input_crates {
-    let source_dir = root_dir.join("native").join(crate_name);
-    watcher.watch(&source_dir, RecursiveMode::Recursive)?;
+  for crate_dir in rinf_config.input_crate_dirs(root_dir) {
+    watcher.watch(&crate_dir, RecursiveMode::Recursive)?;
   }
 
   loop {
@@ -700,14 +961,14 @@ pub fn watch_and_generate_dart_code(
             "Change detected in file `{}`",
             event.paths[0].clean_file_name()?
           );
-          let result = generate_dart_code(root_dir, rinf_config);
+          let result = generate_dart_code(root_dir, rinf_config, schema_file);
           if let Err(err) = result {
-            eprintln!("{err}");
+            report_warning(&err.to_string());
           }
         }
       }
       Err(err) => {
-        eprintln!("{err}");
+        report_warning(&err.to_string());
         break;
       }
     }
@@ -748,6 +1009,18 @@ fn move_directory_contents(
   Ok(())
 }
 
+fn report_generated_files(dir: &Path) -> Result<(), SetupError> {
+  for entry_result in read_dir(dir)? {
+    let path = entry_result?.path();
+    if path.is_dir() {
+      report_generated_files(&path)?;
+    } else {
+      report_generated(&path);
+    }
+  }
+  Ok(())
+}
+
 fn remove_lint_warnings(gen_dir: &Path) -> Result<(), SetupError> {
   // Apply lint ignore to all generated Dart files
   apply_lint_ignore_recursively(gen_dir)?;
@@ -0,0 +1,267 @@
+use crate::dimmedln;
+use crate::tool::{
+  RinfConfig, SetupError, SignalAttribute, Traced, get_dart_class_file,
+  is_json_output, report_json, trace_rust_signals,
+};
+use owo_colors::OwoColorize;
+use serde_reflection::{ContainerFormat, Format, Named, VariantFormat};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Prints every signal found by `rinf gen`
+/// along with its direction, source location, and fields.
+/// In JSON output mode, the full `serde_reflection` registry
+/// is printed instead.
+pub fn inspect_signals(
+  root_dir: &Path,
+  rinf_config: &RinfConfig,
+) -> Result<(), SetupError> {
+  let gen_targets = trace_rust_signals(root_dir, rinf_config)?;
+
+  if is_json_output() {
+    let registry: BTreeMap<&String, &ContainerFormat> = gen_targets
+      .iter()
+      .flat_map(|gen_target| &gen_target.traced.registry)
+      .collect();
+    report_json("registry", &registry);
+    return Ok(());
+  }
+
+  let is_empty = gen_targets
+    .iter()
+    .all(|gen_target| gen_target.traced.registry.is_empty());
+  if is_empty {
+    dimmedln!("No signals were found");
+    return Ok(());
+  }
+
+  // Collect the rows of the summary table.
+  let header = [
+    "Signal".to_owned(),
+    "Direction".to_owned(),
+    "Source".to_owned(),
+    "Dart".to_owned(),
+  ];
+  let mut rows = vec![header];
+  for gen_target in &gen_targets {
+    let traced = &gen_target.traced;
+    let gen_dir = strip_root(root_dir, &gen_target.gen_dir);
+    for (name, signal_attrs) in &traced.signal_attrs {
+      let source = match traced.locations.get(name) {
+        Some(location) => {
+          let file = strip_root(root_dir, &location.file);
+          format!("{}:{}", file.display(), location.line)
+        }
+        None => String::new(),
+      };
+      let dart_file = get_dart_class_file(gen_dir, name);
+      rows.push([
+        name.to_owned(),
+        describe_direction(signal_attrs),
+        source,
+        dart_file.display().to_string(),
+      ]);
+    }
+  }
+
+  // Print the table with aligned columns.
+  let mut widths = [0; 4];
+  for row in &rows {
+    for (width, cell) in widths.iter_mut().zip(row) {
+      *width = (*width).max(cell.chars().count());
+    }
+  }
+  for (index, row) in rows.iter().enumerate() {
+    let line = row
+      .iter()
+      .zip(widths)
+      .map(|(cell, width)| format!("{cell:width$}"))
+      .collect::<Vec<_>>()
+      .join("  ");
+    if index == 0 {
+      println!("{}", line.trim_end().bold());
+    } else {
+      println!("{}", line.trim_end());
+    }
+  }
+
+  // Print the fields of each signal.
+  for gen_target in &gen_targets {
+    let traced = &gen_target.traced;
+    for (name, container) in &traced.registry {
+      println!();
+      println!("{}", name.bold());
+      for line in describe_container(container) {
+        println!("  {line}");
+      }
+      let unresolved = find_unresolved_names(container, traced);
+      if !unresolved.is_empty() {
+        let names: Vec<_> = unresolved.into_iter().collect();
+        let warning = format!("  unresolved: {}", names.join(", "));
+        println!("{}", warning.yellow());
+      }
+    }
+  }
+
+  Ok(())
+}
+
+fn strip_root<'a>(root_dir: &Path, path: &'a Path) -> &'a Path {
+  path.strip_prefix(root_dir).unwrap_or(path)
+}
+
+fn describe_direction(signal_attrs: &BTreeSet<SignalAttribute>) -> String {
+  let names: Vec<&str> = signal_attrs
+    .iter()
+    .map(|signal_attr| match signal_attr {
+      SignalAttribute::SignalPiece => "SignalPiece",
+      SignalAttribute::DartSignal => "DartSignal",
+      SignalAttribute::DartSignalBinary => "DartSignal (binary)",
+      SignalAttribute::RustSignal => "RustSignal",
+      SignalAttribute::RustSignalBinary => "RustSignal (binary)",
+    })
+    .collect();
+  names.join(", ")
+}
+
+fn describe_container(container: &ContainerFormat) -> Vec<String> {
+  match container {
+    ContainerFormat::UnitStruct => vec!["(unit struct)".to_owned()],
+    ContainerFormat::NewTypeStruct(format) => {
+      vec![format!("0: {}", describe_format(format))]
+    }
+    ContainerFormat::TupleStruct(formats) => formats
+      .iter()
+      .enumerate()
+      .map(|(index, format)| format!("{index}: {}", describe_format(format)))
+      .collect(),
+    ContainerFormat::Struct(fields) => describe_fields(fields),
+    ContainerFormat::Enum(variants) => variants
+      .values()
+      .map(|variant| {
+        let description = match &variant.value {
+          VariantFormat::Unit => String::new(),
+          VariantFormat::NewType(format) => {
+            format!("({})", describe_format(format))
+          }
+          VariantFormat::Tuple(formats) => {
+            format!("({})", describe_formats(formats))
+          }
+          VariantFormat::Struct(fields) => {
+            format!(" {{ {} }}", describe_fields(fields).join(", "))
+          }
+          VariantFormat::Variable(_) => "(unknown)".to_owned(),
+        };
+        format!("{}{description}", variant.name)
+      })
+      .collect(),
+  }
+}
+
+fn describe_fields(fields: &[Named<Format>]) -> Vec<String> {
+  fields
+    .iter()
+    .map(|field| format!("{}: {}", field.name, describe_format(&field.value)))
+    .collect()
+}
+
+fn describe_formats(formats: &[Format]) -> String {
+  let descriptions: Vec<String> = formats.iter().map(describe_format).collect();
+  descriptions.join(", ")
+}
+
+/// Describes the resolved format with Rust-like type syntax.
+fn describe_format(format: &Format) -> String {
+  match format {
+    Format::Variable(_) => "(unknown)".to_owned(),
+    Format::TypeName(name) => name.to_owned(),
+    Format::Unit => "()".to_owned(),
+    Format::Bool => "bool".to_owned(),
+    Format::I8 => "i8".to_owned(),
+    Format::I16 => "i16".to_owned(),
+    Format::I32 => "i32".to_owned(),
+    Format::I64 => "i64".to_owned(),
+    Format::I128 => "i128".to_owned(),
+    Format::U8 => "u8".to_owned(),
+    Format::U16 => "u16".to_owned(),
+    Format::U32 => "u32".to_owned(),
+    Format::U64 => "u64".to_owned(),
+    Format::U128 => "u128".to_owned(),
+    Format::F32 => "f32".to_owned(),
+    Format::F64 => "f64".to_owned(),
+    Format::Char => "char".to_owned(),
+    Format::Str => "String".to_owned(),
+    Format::Bytes => "Vec<u8>".to_owned(),
+    Format::Option(inner) => format!("Option<{}>", describe_format(inner)),
+    Format::Seq(inner) => format!("Vec<{}>", describe_format(inner)),
+    Format::Map { key, value } => {
+      format!("Map<{}, {}>", describe_format(key), describe_format(value))
+    }
+    Format::Tuple(formats) => format!("({})", describe_formats(formats)),
+    Format::TupleArray { content, size } => {
+      format!("[{}; {size}]", describe_format(content))
+    }
+  }
+}
+
+/// Finds type names that are used in the container
+/// but are not traced as signals.
+fn find_unresolved_names(
+  container: &ContainerFormat,
+  traced: &Traced,
+) -> BTreeSet<String> {
+  let mut names = BTreeSet::new();
+  let mut formats = Vec::new();
+  match container {
+    ContainerFormat::UnitStruct => {}
+    ContainerFormat::NewTypeStruct(format) => formats.push(format.as_ref()),
+    ContainerFormat::TupleStruct(inner) => formats.extend(inner),
+    ContainerFormat::Struct(fields) => {
+      formats.extend(fields.iter().map(|field| &field.value));
+    }
+    ContainerFormat::Enum(variants) => {
+      for variant in variants.values() {
+        match &variant.value {
+          VariantFormat::NewType(format) => formats.push(format.as_ref()),
+          VariantFormat::Tuple(inner) => formats.extend(inner),
+          VariantFormat::Struct(fields) => {
+            formats.extend(fields.iter().map(|field| &field.value));
+          }
+          VariantFormat::Unit | VariantFormat::Variable(_) => {}
+        }
+      }
+    }
+  }
+  for format in formats {
+    collect_type_names(format, &traced.registry, &mut names);
+  }
+  names
+}
+
+fn collect_type_names(
+  format: &Format,
+  registry: &BTreeMap<String, ContainerFormat>,
+  names: &mut BTreeSet<String>,
+) {
+  match format {
+    Format::TypeName(name) if !registry.contains_key(name) => {
+      names.insert(name.to_owned());
+    }
+    Format::Option(inner) | Format::Seq(inner) => {
+      collect_type_names(inner, registry, names);
+    }
+    Format::Map { key, value } => {
+      collect_type_names(key, registry, names);
+      collect_type_names(value, registry, names);
+    }
+    Format::Tuple(formats) => {
+      for inner in formats {
+        collect_type_names(inner, registry, names);
+      }
+    }
+    Format::TupleArray { content, .. } => {
+      collect_type_names(content, registry, names);
+    }
+    _ => {}
+  }
+}
@@ -0,0 +1,456 @@
+use crate::tool::{
+  FieldsShape, SetupError, get_dart_class_file, insert_class_members,
+  is_c_style, quote_class_name, quote_field_name, quote_variant_class_name,
+};
+use serde_reflection::{ContainerFormat, Format, Named, VariantFormat};
+use std::collections::BTreeMap;
+use std::fs::{read_to_string, write};
+use std::path::Path;
+
+static JSON_HELPERS: &str = r#"
+Object? _bigIntToJson(BigInt value) {
+  return value.isValidInt ? value.toInt() : value.toDouble();
+}
+
+BigInt _bigIntFromJson(Object? json) {
+  return json is String ? BigInt.parse(json) : BigInt.from(json as num);
+}
+
+Object? _optionToJson<T>(T? value, Object? Function(T) toJson) {
+  return value == null ? null : toJson(value);
+}
+
+T? _optionFromJson<T>(Object? json, T Function(Object?) fromJson) {
+  return json == null ? null : fromJson(json);
+}
+"#;
+
+/// Adds `toJson` and `fromJson` to the generated Dart classes,
+/// following serde's default JSON representation.
+pub fn generate_json_code(
+  gen_dir: &Path,
+  gen_mod: &str,
+  registry: &BTreeMap<String, ContainerFormat>,
+) -> Result<(), SetupError> {
+  let generator = JsonGenerator { registry };
+  for (name, container) in registry {
+    let class_file = get_dart_class_file(gen_dir, name);
+    let mut code = read_to_string(&class_file)?;
+    match container {
+      ContainerFormat::Enum(variants) if is_c_style(variants) => {
+        let members = generator.c_style_enum_members(name, variants);
+        let header = format!("extension {name}Extension on ");
+        code = insert_class_members(&code, &header, &members);
+      }
+      ContainerFormat::Enum(variants) => {
+        let members = generator.enum_base_members(name, variants);
+        let header = format!("class {} ", quote_class_name(name));
+        code = insert_class_members(&code, &header, &members);
+        for variant in variants.values() {
+          let item_name = quote_variant_class_name(name, &variant.name);
+          let shape = FieldsShape::from_variant(&variant.value);
+          let members =
+            generator.variant_members(&item_name, &variant.name, &shape);
+          let header = format!("class {item_name} ");
+          code = insert_class_members(&code, &header, &members);
+        }
+      }
+      _ => {
+        if let Some(shape) = FieldsShape::from_container(container) {
+          let class_name = quote_class_name(name);
+          let members = generator.struct_members(&class_name, &shape);
+          let header = format!("class {class_name} ");
+          code = insert_class_members(&code, &header, &members);
+        }
+      }
+    }
+    write(&class_file, code)?;
+  }
+
+  // Write the shared helper functions.
+  let helpers_file = gen_dir.join(gen_mod).join("json_helpers.dart");
+  write(
+    helpers_file,
+    format!("part of '{gen_mod}.dart';\n{JSON_HELPERS}"),
+  )?;
+  let top_file = gen_dir.join(gen_mod).join(format!("{gen_mod}.dart"));
+  let mut top_content = read_to_string(&top_file)?;
+  top_content.push_str("part 'json_helpers.dart';\n");
+  write(&top_file, top_content)?;
+
+  Ok(())
+}
+
+struct JsonGenerator<'a> {
+  registry: &'a BTreeMap<String, ContainerFormat>,
+}
+
+impl JsonGenerator<'_> {
+  fn struct_members(&self, class_name: &str, shape: &FieldsShape) -> String {
+    let to_json = self.shape_to_json(shape);
+    let from_json = self.shape_from_json(class_name, shape, "json");
+    format!(
+      r#"  /// Converts this object into a JSON-compatible value
+  /// following serde's default JSON representation.
+  Object? toJson() {{
+    return {to_json};
+  }}
+
+  /// Creates an object from a JSON-compatible value
+  /// following serde's default JSON representation.
+  static {class_name} fromJson(Object? json) {{
+{from_json}  }}
+
+"#
+    )
+  }
+
+  fn variant_members(
+    &self,
+    item_name: &str,
+    variant_name: &str,
+    shape: &FieldsShape,
+  ) -> String {
+    let to_json = match shape {
+      FieldsShape::Unit => format!("'{variant_name}'"),
+      _ => format!(
+        "<String, Object?>{{'{variant_name}': {}}}",
+        self.shape_to_json(shape)
+      ),
+    };
+    let from_json = self.shape_from_json(item_name, shape, "json");
+    format!(
+      r#"  @override
+  Object? toJson() {{
+    return {to_json};
+  }}
+
+  static {item_name} fromJson(Object? json) {{
+{from_json}  }}
+
+"#
+    )
+  }
+
+  fn enum_base_members(
+    &self,
+    name: &str,
+    variants: &BTreeMap<u32, Named<VariantFormat>>,
+  ) -> String {
+    let class_name = quote_class_name(name);
+    let mut unit_cases = String::new();
+    let mut data_cases = String::new();
+    for variant in variants.values() {
+      let item_name = quote_variant_class_name(name, &variant.name);
+      if matches!(variant.value, VariantFormat::Unit) {
+        unit_cases.push_str(&format!(
+          "        case '{}':\n          return {item_name}.fromJson(null);\n",
+          variant.name
+        ));
+      } else {
+        data_cases.push_str(&format!(
+          "        case '{}':\n          return {item_name}.fromJson(entry.value);\n",
+          variant.name
+        ));
+      }
+    }
+    format!(
+      r#"  /// Converts this object into a JSON-compatible value
+  /// following serde's default JSON representation.
+  Object? toJson();
+
+  /// Creates an object from a JSON-compatible value
+  /// following serde's default JSON representation.
+  static {class_name} fromJson(Object? json) {{
+    if (json is String) {{
+      switch (json) {{
+{unit_cases}      }}
+    }} else if (json is Map<String, Object?> && json.length == 1) {{
+      final entry = json.entries.first;
+      switch (entry.key) {{
+{data_cases}      }}
+    }}
+    throw FormatException('Unknown variant for {name}: $json');
+  }}
+
+"#
+    )
+  }
+
+  fn c_style_enum_members(
+    &self,
+    name: &str,
+    variants: &BTreeMap<u32, Named<VariantFormat>>,
+  ) -> String {
+    let class_name = quote_class_name(name);
+    let mut to_cases = String::new();
+    let mut from_cases = String::new();
+    for variant in variants.values() {
+      let value_name = quote_field_name(&variant.name);
+      to_cases.push_str(&format!(
+        "      case {class_name}.{value_name}:\n        return '{}';\n",
+        variant.name
+      ));
+      from_cases.push_str(&format!(
+        "      case '{}':\n        return {class_name}.{value_name};\n",
+        variant.name
+      ));
+    }
+    format!(
+      r#"  /// Converts this value into a JSON-compatible value
+  /// following serde's default JSON representation.
+  Object? toJson() {{
+    switch (this) {{
+{to_cases}    }}
+  }}
+
+  /// Creates a value from a JSON-compatible value
+  /// following serde's default JSON representation.
+  static {class_name} fromJson(Object? json) {{
+    switch (json) {{
+{from_cases}    }}
+    throw FormatException('Unknown variant for {name}: $json');
+  }}
+
+"#
+    )
+  }
+
+  /// Returns the expression that converts the fields into JSON.
+  fn shape_to_json(&self, shape: &FieldsShape) -> String {
+    match shape {
+      FieldsShape::Unit => "null".to_owned(),
+      FieldsShape::NewType(format) => self.value_to_json(format, "value", 0),
+      FieldsShape::Tuple(formats) => {
+        let items: Vec<String> = formats
+          .iter()
+          .enumerate()
+          .map(|(index, format)| {
+            self.value_to_json(format, &format!("field{index}"), 0)
+          })
+          .collect();
+        format!("<Object?>[{}]", items.join(", "))
+      }
+      FieldsShape::Struct(fields) => {
+        let entries: Vec<String> = fields
+          .iter()
+          .map(|field| {
+            let field_name = quote_field_name(&field.name);
+            let value = self.value_to_json(&field.value, &field_name, 0);
+            format!("'{}': {value}", field.name)
+          })
+          .collect();
+        format!("<String, Object?>{{{}}}", entries.join(", "))
+      }
+    }
+  }
+
+  /// Returns the statements that construct the class from JSON.
+  fn shape_from_json(
+    &self,
+    class_name: &str,
+    shape: &FieldsShape,
+    json: &str,
+  ) -> String {
+    match shape {
+      FieldsShape::Unit => format!("    return {class_name}();\n"),
+      FieldsShape::NewType(format) => {
+        let value = self.value_from_json(format, json, 0);
+        format!("    return {class_name}(value: {value});\n")
+      }
+      FieldsShape::Tuple(formats) => {
+        let args: Vec<String> = formats
+          .iter()
+          .enumerate()
+          .map(|(index, format)| {
+            let item = format!("list[{index}]");
+            format!("field{index}: {}", self.value_from_json(format, &item, 0))
+          })
+          .collect();
+        format!(
+          "    final list = {json} as List<Object?>;\n    \
+          return {class_name}({});\n",
+          args.join(", ")
+        )
+      }
+      FieldsShape::Struct(fields) => {
+        let args: Vec<String> = fields
+          .iter()
+          .map(|field| {
+            let item = format!("map['{}']", field.name);
+            let value = self.value_from_json(&field.value, &item, 0);
+            format!("{}: {value}", quote_field_name(&field.name))
+          })
+          .collect();
+        format!(
+          "    final map = {json} as Map<String, Object?>;\n    \
+          return {class_name}({});\n",
+          args.join(", ")
+        )
+      }
+    }
+  }
+
+  /// Returns the Dart expression that converts a value into JSON.
+  fn value_to_json(
+    &self,
+    format: &Format,
+    value: &str,
+    depth: usize,
+  ) -> String {
+    let e = format!("e{depth}");
+    match format {
+      Format::TypeName(_) => format!("{value}.toJson()"),
+      Format::Unit => "null".to_owned(),
+      Format::U64 | Format::I128 | Format::U128 => {
+        format!("_bigIntToJson({value}.toBigInt())")
+      }
+      Format::Char => format!("String.fromCharCode({value})"),
+      Format::Bytes => format!("{value}.content.toList()"),
+      Format::Option(inner) => {
+        let inner_json = self.value_to_json(inner, &e, depth + 1);
+        format!("_optionToJson({value}, ({e}) => {inner_json})")
+      }
+      Format::Seq(inner) | Format::TupleArray { content: inner, .. } => {
+        let inner_json = self.value_to_json(inner, &e, depth + 1);
+        if inner_json == e {
+          // Lists of plain values are already JSON-compatible.
+          return value.to_owned();
+        }
+        format!("{value}.map(({e}) => {inner_json}).toList()")
+      }
+      Format::Map { key, value: item } => {
+        let k = format!("k{depth}");
+        let key_json = self.key_to_json(key, &k);
+        let item_json = self.value_to_json(item, &e, depth + 1);
+        if key_json == k && item_json == e {
+          return value.to_owned();
+        }
+        format!("{value}.map(({k}, {e}) => MapEntry({key_json}, {item_json}))")
+      }
+      Format::Tuple(formats) => {
+        let items: Vec<String> = formats
+          .iter()
+          .enumerate()
+          .map(|(index, inner)| {
+            let item = format!("{value}.item{}", index + 1);
+            self.value_to_json(inner, &item, depth + 1)
+          })
+          .collect();
+        format!("<Object?>[{}]", items.join(", "))
+      }
+      _ => value.to_owned(),
+    }
+  }
+
+  /// Returns the Dart expression that converts JSON into a value.
+  fn value_from_json(
+    &self,
+    format: &Format,
+    json: &str,
+    depth: usize,
+  ) -> String {
+    let e = format!("e{depth}");
+    match format {
+      Format::TypeName(name) => match self.registry.get(name) {
+        Some(ContainerFormat::Enum(variants)) if is_c_style(variants) => {
+          format!("{name}Extension.fromJson({json})")
+        }
+        _ => format!("{}.fromJson({json})", quote_class_name(name)),
+      },
+      Format::Unit => "const Unit()".to_owned(),
+      Format::Bool => format!("{json} as bool"),
+      Format::I8
+      | Format::I16
+      | Format::I32
+      | Format::I64
+      | Format::U8
+      | Format::U16
+      | Format::U32 => format!("({json} as num).toInt()"),
+      Format::F32 | Format::F64 => format!("({json} as num).toDouble()"),
+      Format::U64 => format!("Uint64.fromBigInt(_bigIntFromJson({json}))"),
+      Format::I128 => format!("Int128.fromBigInt(_bigIntFromJson({json}))"),
+      Format::U128 => format!("Uint128.fromBigInt(_bigIntFromJson({json}))"),
+      Format::Char => format!("({json} as String).runes.first"),
+      Format::Str => format!("{json} as String"),
+      Format::Bytes => {
+        format!("Bytes(Uint8List.fromList(({json} as List).cast<int>()))")
+      }
+      Format::Option(inner) => {
+        let inner_value = self.value_from_json(inner, &e, depth + 1);
+        format!("_optionFromJson({json}, ({e}) => {inner_value})")
+      }
+      Format::Seq(inner) | Format::TupleArray { content: inner, .. } => {
+        let inner_value = self.value_from_json(inner, &e, depth + 1);
+        format!(
+          "({json} as List<Object?>).map(({e}) => {inner_value}).toList()"
+        )
+      }
+      Format::Map { key, value } => {
+        let k = format!("k{depth}");
+        let key_value = self.key_from_json(key, &k);
+        let item_value = self.value_from_json(value, &e, depth + 1);
+        format!(
+          "({json} as Map<String, Object?>)\
+          .map(({k}, {e}) => MapEntry({key_value}, {item_value}))"
+        )
+      }
+      Format::Tuple(formats) => {
+        let list = format!("l{depth}");
+        let items: Vec<String> = formats
+          .iter()
+          .enumerate()
+          .map(|(index, inner)| {
+            let item = format!("{list}[{index}]");
+            self.value_from_json(inner, &item, depth + 1)
+          })
+          .collect();
+        format!(
+          "((List<Object?> {list}) => Tuple{}({}))({json} as List<Object?>)",
+          formats.len(),
+          items.join(", ")
+        )
+      }
+      Format::Variable(_) => json.to_owned(),
+    }
+  }
+
+  /// Map keys are always strings in JSON.
+  fn key_to_json(&self, format: &Format, key: &str) -> String {
+    match format {
+      Format::Str => key.to_owned(),
+      Format::Char => format!("String.fromCharCode({key})"),
+      Format::Bool
+      | Format::I8
+      | Format::I16
+      | Format::I32
+      | Format::I64
+      | Format::I128
+      | Format::U8
+      | Format::U16
+      | Format::U32
+      | Format::U64
+      | Format::U128 => format!("{key}.toString()"),
+      _ => "throw UnsupportedError('JSON map keys must be strings')".to_owned(),
+    }
+  }
+
+  fn key_from_json(&self, format: &Format, key: &str) -> String {
+    match format {
+      Format::Str => key.to_owned(),
+      Format::Char => format!("{key}.runes.first"),
+      Format::Bool => format!("{key} == 'true'"),
+      Format::I8
+      | Format::I16
+      | Format::I32
+      | Format::I64
+      | Format::U8
+      | Format::U16
+      | Format::U32 => format!("int.parse({key})"),
+      Format::U64 => format!("Uint64.parse({key})"),
+      Format::I128 => format!("Int128.parse({key})"),
+      Format::U128 => format!("Uint128.parse({key})"),
+      _ => "throw UnsupportedError('JSON map keys must be strings')".to_owned(),
+    }
+  }
+}
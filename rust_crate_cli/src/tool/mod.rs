@@ -1,17 +1,33 @@
 mod common;
 mod config;
+mod dart;
+mod endpoint;
 mod entry;
 mod error;
 mod generate;
+mod inspect;
+mod json;
+mod monitor;
+mod report;
+mod schema;
+mod sealed;
 mod server;
 mod template;
 mod webassembly;
 
 pub use common::*;
 pub use config::*;
+pub use dart::*;
+pub use endpoint::*;
 pub use entry::*;
 pub use error::*;
 pub use generate::*;
+pub use inspect::*;
+pub use json::*;
+pub use monitor::*;
+pub use report::*;
+pub use schema::*;
+pub use sealed::*;
 pub use server::*;
 pub use template::*;
 pub use webassembly::*;
@@ -0,0 +1,402 @@
+use crate::dimmedln;
+use crate::tool::{
+  RinfConfig, SetupError, build_glob_set, is_json_output, read_endpoint_ids,
+  report_json, trace_rust_signals,
+};
+use clap::ValueEnum;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use serde_reflection::{ContainerFormat, Format, Named, VariantFormat};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tungstenite::error::ProtocolError;
+use tungstenite::{Error as TungsteniteError, Message, connect};
+
+/// A signal mirrored by the inspector of the running app.
+#[derive(Serialize)]
+struct MonitoredSignal {
+  /// Microseconds since the Unix epoch.
+  timestamp: u64,
+  direction: &'static str,
+  endpoint_id: u32,
+  name: Option<String>,
+  message: Option<Value>,
+  binary_size: u64,
+}
+
+/// Which way a signal was going.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum SignalDirection {
+  DartToRust,
+  RustToDart,
+}
+
+impl SignalDirection {
+  fn as_str(&self) -> &'static str {
+    match self {
+      Self::DartToRust => "dart_to_rust",
+      Self::RustToDart => "rust_to_dart",
+    }
+  }
+}
+
+/// Which signals to show in the monitor.
+pub struct MonitorFilter {
+  /// Glob patterns of signal names.
+  pub patterns: Vec<String>,
+  pub direction: Option<SignalDirection>,
+}
+
+/// Connects to the signal inspector of a running app in debug mode
+/// and prints each signal crossing the bridge as it arrives.
+/// Messages are decoded with the type registry traced from Rust files.
+/// This function blocks the thread until the app exits.
+pub fn monitor_signals(
+  root_dir: &Path,
+  rinf_config: &RinfConfig,
+  address: &str,
+  filter: &MonitorFilter,
+) -> Result<(), SetupError> {
+  let gen_targets = trace_rust_signals(root_dir, rinf_config)?;
+  // Endpoint IDs are the ones that `rinf gen` wrote for the running app.
+  let names: BTreeMap<u32, String> = read_endpoint_ids(&gen_targets)
+    .into_iter()
+    .map(|(name, id)| (id, name))
+    .collect();
+  let mut registry = BTreeMap::new();
+  for gen_target in gen_targets {
+    registry.extend(gen_target.traced.registry);
+  }
+  let glob_set = build_glob_set(&filter.patterns, SetupError::BadFilter)?;
+
+  let (mut web_socket, _) = connect(format!("ws://{address}"))?;
+  dimmedln!("Monitoring signals from {address}");
+  dimmedln!("Press Ctrl+C to stop monitoring");
+
+  loop {
+    let encoded = match web_socket.read() {
+      Ok(Message::Binary(inner)) => inner,
+      Ok(Message::Close(_)) => break,
+      Ok(_) => continue,
+      // The app can exit without closing the connection properly.
+      Err(
+        TungsteniteError::ConnectionClosed
+        | TungsteniteError::AlreadyClosed
+        | TungsteniteError::Protocol(ProtocolError::ResetWithoutClosingHandshake),
+      ) => break,
+      Err(err) => return Err(err.into()),
+    };
+    let Some(signal) = decode_envelope(&encoded, &names, &registry) else {
+      continue;
+    };
+    if let Some(direction) = filter.direction
+      && signal.direction != direction.as_str()
+    {
+      continue;
+    }
+    if !filter.patterns.is_empty() {
+      let name = signal.name.as_deref().unwrap_or_default();
+      if !glob_set.is_match(name) {
+        continue;
+      }
+    }
+    print_signal(&signal);
+  }
+
+  dimmedln!("The app has disconnected");
+  Ok(())
+}
+
+fn print_signal(signal: &MonitoredSignal) {
+  if is_json_output() {
+    report_json("signal", signal);
+    return;
+  }
+  let seconds = signal.timestamp / 1_000_000;
+  let time = format!(
+    "{:02}:{:02}:{:02}.{:03}",
+    seconds / 3600 % 24,
+    seconds / 60 % 60,
+    seconds % 60,
+    signal.timestamp / 1000 % 1000,
+  );
+  let arrow = match signal.direction {
+    "dart_to_rust" => "Dart → Rust",
+    _ => "Rust → Dart",
+  };
+  let name = match &signal.name {
+    Some(name) => name.to_owned(),
+    None => format!("#{}", signal.endpoint_id),
+  };
+  let message = match &signal.message {
+    Some(value) => value.to_string(),
+    None => "<unknown>".to_owned(),
+  };
+  let binary = match signal.binary_size {
+    0 => String::new(),
+    size => format!(" +{size} bytes"),
+  };
+  println!(
+    "{} {} {} {}{}",
+    time.dimmed(),
+    arrow.dimmed(),
+    name.bold(),
+    message,
+    binary.dimmed(),
+  );
+}
+
+/// Decodes a WebSocket message from the inspector,
+/// which is written by the Rinf crate in bincode.
+fn decode_envelope(
+  encoded: &[u8],
+  names: &BTreeMap<u32, String>,
+  registry: &BTreeMap<String, ContainerFormat>,
+) -> Option<MonitoredSignal> {
+  let mut reader = BincodeReader { bytes: encoded };
+  let timestamp = reader.read_u64()?;
+  let direction = match reader.read_u8()? {
+    0 => SignalDirection::DartToRust,
+    _ => SignalDirection::RustToDart,
+  }
+  .as_str();
+  let endpoint_id = reader.read_u32()?;
+  let message_size = reader.read_length()?;
+  let message_bytes = reader.take(message_size)?;
+  let binary_size = reader.read_u64()?;
+
+  let name = names.get(&endpoint_id).cloned();
+  let message = name.as_ref().and_then(|name| {
+    let container = registry.get(name)?;
+    let mut reader = BincodeReader {
+      bytes: message_bytes,
+    };
+    reader.read_container(container, registry)
+  });
+  Some(MonitoredSignal {
+    timestamp,
+    direction,
+    endpoint_id,
+    name,
+    message,
+    binary_size,
+  })
+}
+
+/// Reads values in the bincode format used by Rinf,
+/// following the shapes in the type registry.
+/// Values are converted to serde's default JSON representation.
+struct BincodeReader<'a> {
+  bytes: &'a [u8],
+}
+
+impl<'a> BincodeReader<'a> {
+  fn take(&mut self, size: usize) -> Option<&'a [u8]> {
+    if self.bytes.len() < size {
+      return None;
+    }
+    let (taken, rest) = self.bytes.split_at(size);
+    self.bytes = rest;
+    Some(taken)
+  }
+
+  fn take_array<const N: usize>(&mut self) -> Option<[u8; N]> {
+    self.take(N)?.try_into().ok()
+  }
+
+  fn read_u8(&mut self) -> Option<u8> {
+    Some(self.take_array::<1>()?[0])
+  }
+
+  fn read_u32(&mut self) -> Option<u32> {
+    Some(u32::from_le_bytes(self.take_array()?))
+  }
+
+  fn read_u64(&mut self) -> Option<u64> {
+    Some(u64::from_le_bytes(self.take_array()?))
+  }
+
+  fn read_length(&mut self) -> Option<usize> {
+    usize::try_from(self.read_u64()?).ok()
+  }
+
+  fn read_str(&mut self) -> Option<String> {
+    let size = self.read_length()?;
+    let bytes = self.take(size)?;
+    String::from_utf8(bytes.to_vec()).ok()
+  }
+
+  fn read_char(&mut self) -> Option<String> {
+    // Characters are written as UTF-8 bytes,
+    // and the first byte tells the length.
+    let first = *self.bytes.first()?;
+    let size = match first {
+      0x00..=0x7F => 1,
+      0xC0..=0xDF => 2,
+      0xE0..=0xEF => 3,
+      _ => 4,
+    };
+    let bytes = self.take(size)?;
+    String::from_utf8(bytes.to_vec()).ok()
+  }
+
+  fn float_value(value: f64) -> Value {
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+  }
+
+  fn read_format(
+    &mut self,
+    format: &Format,
+    registry: &BTreeMap<String, ContainerFormat>,
+  ) -> Option<Value> {
+    let value = match format {
+      Format::Variable(_) => return None,
+      Format::TypeName(name) => {
+        let container = registry.get(name)?;
+        self.read_container(container, registry)?
+      }
+      Format::Unit => Value::Null,
+      Format::Bool => Value::Bool(self.read_u8()? != 0),
+      Format::I8 => i8::from_le_bytes(self.take_array()?).into(),
+      Format::I16 => i16::from_le_bytes(self.take_array()?).into(),
+      Format::I32 => i32::from_le_bytes(self.take_array()?).into(),
+      Format::I64 => i64::from_le_bytes(self.take_array()?).into(),
+      Format::I128 => {
+        Value::String(i128::from_le_bytes(self.take_array()?).to_string())
+      }
+      Format::U8 => self.read_u8()?.into(),
+      Format::U16 => u16::from_le_bytes(self.take_array()?).into(),
+      Format::U32 => self.read_u32()?.into(),
+      Format::U64 => self.read_u64()?.into(),
+      Format::U128 => {
+        Value::String(u128::from_le_bytes(self.take_array()?).to_string())
+      }
+      Format::F32 => {
+        Self::float_value(f32::from_le_bytes(self.take_array()?).into())
+      }
+      Format::F64 => Self::float_value(f64::from_le_bytes(self.take_array()?)),
+      Format::Char => Value::String(self.read_char()?),
+      Format::Str => Value::String(self.read_str()?),
+      Format::Bytes => {
+        let size = self.read_length()?;
+        let bytes = self.take(size)?;
+        Value::Array(bytes.iter().map(|byte| (*byte).into()).collect())
+      }
+      Format::Option(inner) => match self.read_u8()? {
+        0 => Value::Null,
+        _ => self.read_format(inner, registry)?,
+      },
+      Format::Seq(inner) => {
+        let size = self.read_length()?;
+        self.read_items(size, inner, registry)?
+      }
+      Format::Map { key, value } => {
+        let size = self.read_length()?;
+        let mut entries = Vec::with_capacity(size);
+        for _ in 0..size {
+          let key = self.read_format(key, registry)?;
+          let value = self.read_format(value, registry)?;
+          entries.push((key, value));
+        }
+        // Maps with string keys become JSON objects,
+        // and other maps become lists of key-value pairs.
+        if entries.iter().all(|(key, _)| key.is_string()) {
+          let object: Map<String, Value> = entries
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.as_str()?.to_owned(), value)))
+            .collect();
+          Value::Object(object)
+        } else {
+          Value::Array(
+            entries
+              .into_iter()
+              .map(|(key, value)| Value::Array(vec![key, value]))
+              .collect(),
+          )
+        }
+      }
+      Format::Tuple(formats) => self.read_tuple(formats, registry)?,
+      Format::TupleArray { content, size } => {
+        self.read_items(*size, content, registry)?
+      }
+    };
+    Some(value)
+  }
+
+  fn read_items(
+    &mut self,
+    size: usize,
+    format: &Format,
+    registry: &BTreeMap<String, ContainerFormat>,
+  ) -> Option<Value> {
+    let items: Option<Vec<Value>> = (0..size)
+      .map(|_| self.read_format(format, registry))
+      .collect();
+    Some(Value::Array(items?))
+  }
+
+  fn read_tuple(
+    &mut self,
+    formats: &[Format],
+    registry: &BTreeMap<String, ContainerFormat>,
+  ) -> Option<Value> {
+    let items: Option<Vec<Value>> = formats
+      .iter()
+      .map(|format| self.read_format(format, registry))
+      .collect();
+    Some(Value::Array(items?))
+  }
+
+  fn read_fields(
+    &mut self,
+    fields: &[Named<Format>],
+    registry: &BTreeMap<String, ContainerFormat>,
+  ) -> Option<Value> {
+    let mut object = Map::new();
+    for field in fields {
+      let value = self.read_format(&field.value, registry)?;
+      object.insert(field.name.to_owned(), value);
+    }
+    Some(Value::Object(object))
+  }
+
+  fn read_container(
+    &mut self,
+    container: &ContainerFormat,
+    registry: &BTreeMap<String, ContainerFormat>,
+  ) -> Option<Value> {
+    match container {
+      ContainerFormat::UnitStruct => Some(Value::Null),
+      ContainerFormat::NewTypeStruct(format) => {
+        self.read_format(format, registry)
+      }
+      ContainerFormat::TupleStruct(formats) => {
+        self.read_tuple(formats, registry)
+      }
+      ContainerFormat::Struct(fields) => self.read_fields(fields, registry),
+      ContainerFormat::Enum(variants) => {
+        let index = self.read_u32()?;
+        let variant = variants.get(&index)?;
+        let content = match &variant.value {
+          VariantFormat::Variable(_) => return None,
+          VariantFormat::Unit => {
+            return Some(Value::String(variant.name.to_owned()));
+          }
+          VariantFormat::NewType(format) => {
+            self.read_format(format, registry)?
+          }
+          VariantFormat::Tuple(formats) => {
+            self.read_tuple(formats, registry)?
+          }
+          VariantFormat::Struct(fields) => {
+            self.read_fields(fields, registry)?
+          }
+        };
+        let mut object = Map::new();
+        object.insert(variant.name.to_owned(), content);
+        Some(Value::Object(object))
+      }
+    }
+  }
+}
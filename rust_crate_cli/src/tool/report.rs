@@ -0,0 +1,132 @@
+use crate::SetupError;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the CLI output should be machine-readable.
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Switches all CLI output to JSON lines, one event per line.
+pub fn set_json_output(enabled: bool) {
+  JSON_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_json_output() -> bool {
+  JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// A structured event printed as a JSON line in JSON output mode.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ReportEvent<'a, T: Serialize> {
+  Info {
+    message: &'a str,
+  },
+  Progress {
+    step: usize,
+    total: usize,
+    message: &'a str,
+  },
+  Warning {
+    message: &'a str,
+  },
+  Generated {
+    path: &'a Path,
+  },
+  Success {
+    message: &'a str,
+  },
+  Data {
+    kind: &'a str,
+    data: &'a T,
+  },
+  Error {
+    code: &'a str,
+    exit_code: u8,
+    message: &'a str,
+  },
+}
+
+fn print_event<T: Serialize>(event: &ReportEvent<T>) {
+  match serde_json::to_string(event) {
+    Ok(line) => println!("{line}"),
+    Err(err) => eprintln!("{err}"),
+  }
+}
+
+/// Prints a supplementary message.
+pub fn report_info(message: &str) {
+  if is_json_output() {
+    print_event::<()>(&ReportEvent::Info { message });
+  } else {
+    println!("{}", message.dimmed());
+  }
+}
+
+/// Prints a step of a long-running task.
+pub fn report_progress(step: usize, total: usize, message: &str) {
+  if is_json_output() {
+    print_event::<()>(&ReportEvent::Progress {
+      step,
+      total,
+      message,
+    });
+  } else {
+    println!("{}", format!("[{step}/{total}] {message}").dimmed());
+  }
+}
+
+/// Prints a problem that doesn't stop the command.
+pub fn report_warning(message: &str) {
+  if is_json_output() {
+    print_event::<()>(&ReportEvent::Warning { message });
+  } else {
+    eprintln!("{}", message.red());
+  }
+}
+
+/// Notifies that a file was generated.
+/// This only has an effect in JSON output mode.
+pub fn report_generated(path: &Path) {
+  if is_json_output() {
+    print_event::<()>(&ReportEvent::Generated { path });
+  }
+}
+
+/// Prints the final result of a command.
+pub fn report_success(message: &str) {
+  if is_json_output() {
+    print_event::<()>(&ReportEvent::Success { message });
+  } else {
+    println!("{message}");
+  }
+}
+
+/// Prints structured data as JSON, or as text in normal output mode.
+pub fn report_data<T: Serialize>(kind: &str, data: &T, text: &str) {
+  if is_json_output() {
+    report_json(kind, data);
+  } else {
+    println!("{text}");
+  }
+}
+
+/// Prints structured data as JSON regardless of the output mode.
+pub fn report_json<T: Serialize>(kind: &str, data: &T) {
+  print_event(&ReportEvent::Data { kind, data });
+}
+
+/// Prints the error that stopped the command.
+pub fn report_error(err: &SetupError) {
+  if is_json_output() {
+    let message = err.to_string();
+    print_event::<()>(&ReportEvent::Error {
+      code: err.code(),
+      exit_code: err.exit_code(),
+      message: &message,
+    });
+  } else {
+    eprintln!("{}", format!("Error: {err}").red());
+  }
+}
@@ -0,0 +1,115 @@
+use crate::tool::{GenTarget, SetupError, SignalAttribute, report_generated};
+use serde::Serialize;
+use serde_reflection::ContainerFormat;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{create_dir_all, write};
+use std::path::Path;
+
+/// Version of the schema document format.
+/// This is increased only when the format changes incompatibly.
+static SCHEMA_VERSION: u32 = 1;
+
+/// A language-neutral description of all signals,
+/// meant to be consumed by other tools.
+#[derive(Serialize)]
+struct SchemaDocument<'a> {
+  schema_version: u32,
+  signals: BTreeMap<&'a str, SignalSchema<'a>>,
+}
+
+#[derive(Serialize)]
+struct SignalSchema<'a> {
+  /// Signal attributes, such as `DartSignal` or `RustSignal`.
+  directions: Option<&'a BTreeSet<SignalAttribute>>,
+  /// Directory of the generated Dart code, relative to the project root.
+  output_dir: String,
+  source: Option<SchemaSource>,
+  /// Doc comment of the type itself.
+  doc: Option<&'a str>,
+  /// Doc comments of fields and variants, keyed by their dotted paths.
+  member_docs: BTreeMap<String, &'a str>,
+  /// The format traced by `serde_reflection`.
+  format: &'a ContainerFormat,
+}
+
+#[derive(Serialize)]
+struct SchemaSource {
+  /// Rust file path, relative to the project root.
+  file: String,
+  line: usize,
+}
+
+/// Writes the traced signals as a schema document.
+/// The file is written as YAML if its extension is `yaml` or `yml`,
+/// and as JSON otherwise.
+pub fn write_signal_schema(
+  root_dir: &Path,
+  schema_file: &Path,
+  gen_targets: &[GenTarget],
+) -> Result<(), SetupError> {
+  let mut signals = BTreeMap::new();
+  for gen_target in gen_targets {
+    let traced = &gen_target.traced;
+    let output_dir = relative_path(root_dir, &gen_target.gen_dir);
+    for (name, format) in &traced.registry {
+      let source = traced.locations.get(name).map(|location| SchemaSource {
+        file: relative_path(root_dir, &location.file),
+        line: location.line,
+      });
+      let mut doc = None;
+      let mut member_docs = BTreeMap::new();
+      for (item_path, doc_comment) in &traced.doc_comments {
+        let doc_comment = doc_comment.trim();
+        if doc_comment.is_empty() {
+          continue;
+        }
+        match item_path.get(1..) {
+          Some([item_name]) if item_name == name => doc = Some(doc_comment),
+          Some([item_name, members @ ..]) if item_name == name => {
+            member_docs.insert(members.join("."), doc_comment);
+          }
+          _ => {}
+        }
+      }
+      let signal_schema = SignalSchema {
+        directions: traced.signal_attrs.get(name),
+        output_dir: output_dir.clone(),
+        source,
+        doc,
+        member_docs,
+        format,
+      };
+      signals.insert(name.as_str(), signal_schema);
+    }
+  }
+  let document = SchemaDocument {
+    schema_version: SCHEMA_VERSION,
+    signals,
+  };
+
+  let extension = schema_file
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .unwrap_or_default();
+  let content = match extension {
+    // YAML cannot represent nested enums directly,
+    // so the document goes through a JSON value first.
+    "yaml" | "yml" => {
+      serde_norway::to_string(&serde_json::to_value(&document)?)?
+    }
+    _ => serde_json::to_string_pretty(&document)? + "\n",
+  };
+  if let Some(parent) = schema_file.parent() {
+    create_dir_all(parent)?;
+  }
+  write(schema_file, content)?;
+  report_generated(schema_file);
+  Ok(())
+}
+
+/// Returns a path relative to the project root with forward slashes,
+/// so that the document is the same on every platform.
+fn relative_path(root_dir: &Path, path: &Path) -> String {
+  let relative = path.strip_prefix(root_dir).unwrap_or(path);
+  relative.to_string_lossy().replace('\\', "/")
+}
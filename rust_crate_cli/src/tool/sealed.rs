@@ -0,0 +1,46 @@
+use crate::tool::{
+  SetupError, get_dart_class_file, is_c_style, quote_class_name,
+  quote_variant_class_name,
+};
+use serde_reflection::ContainerFormat;
+use std::collections::BTreeMap;
+use std::fs::{read_to_string, write};
+use std::path::Path;
+
+/// Turns the generated Dart classes into sealed class hierarchies.
+/// Enums with data get a `sealed` base class and `final` variant classes,
+/// so that Dart 3 can check `switch` statements for exhaustiveness.
+/// Constructors and `copyWith` are already generated by `serde-generate`.
+/// Only the Dart API changes, so the bincode layout stays the same.
+pub fn generate_sealed_code(
+  gen_dir: &Path,
+  registry: &BTreeMap<String, ContainerFormat>,
+) -> Result<(), SetupError> {
+  for (name, container) in registry {
+    // Dart enums from C-style Rust enums already support exhaustive `switch`.
+    let ContainerFormat::Enum(variants) = container else {
+      continue;
+    };
+    if is_c_style(variants) {
+      continue;
+    }
+    let class_file = get_dart_class_file(gen_dir, name);
+    let mut code = read_to_string(&class_file)?;
+    let class_name = quote_class_name(name);
+    code = code.replacen(
+      &format!("abstract class {class_name} "),
+      &format!("sealed class {class_name} "),
+      1,
+    );
+    for variant in variants.values() {
+      let item_name = quote_variant_class_name(name, &variant.name);
+      code = code.replacen(
+        &format!("\nclass {item_name} "),
+        &format!("\nfinal class {item_name} "),
+        1,
+      );
+    }
+    write(&class_file, code)?;
+  }
+  Ok(())
+}
@@ -1,15 +1,217 @@
 use crate::SetupError;
+use crate::dimmedln;
+use crate::tool::{report_data, report_warning};
 use arboard::Clipboard;
+use std::fs::read;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::thread::spawn;
 
+/// Headers that enable `SharedArrayBuffer` in the browser,
+/// which is required by the multi-threaded WebAssembly module.
+static CROSS_ORIGIN_HEADERS: [(&str, &str); 2] = [
+  ("cross-origin-opener-policy", "same-origin"),
+  ("cross-origin-embedder-policy", "require-corp"),
+];
+
+/// Copies the full `flutter run` command to the clipboard.
+/// If the clipboard is not available, like on headless CI machines
+/// or over SSH, the command is printed to stdout instead.
 pub fn provide_server_command(release: bool) -> Result<(), SetupError> {
-  let mut clipboard = Clipboard::new()?;
   let release_arg = if release { " --release" } else { "" };
-  let base_command = concat!(
-    "flutter run",
-    " --web-header=cross-origin-opener-policy=same-origin",
-    " --web-header=cross-origin-embedder-policy=require-corp",
+  let header_args: String = CROSS_ORIGIN_HEADERS
+    .iter()
+    .map(|(name, value)| format!(" --web-header={name}={value}"))
+    .collect();
+  let full_command = format!("flutter run{header_args}{release_arg}");
+  match copy_to_clipboard(&full_command) {
+    Ok(()) => {
+      let full_guide = concat!(
+        "Full `flutter run` command for the web",
+        " has been copied to the clipboard"
+      );
+      dimmedln!("{}", full_guide);
+    }
+    Err(err) => {
+      report_warning(&err.to_string());
+      dimmedln!("Full `flutter run` command for the web is shown below");
+      report_data("flutter_command", &full_command, &full_command);
+    }
+  }
+  Ok(())
+}
+
+fn copy_to_clipboard(text: &str) -> Result<(), SetupError> {
+  let mut clipboard = Clipboard::new()?;
+  clipboard.set_text(text)?;
+  Ok(())
+}
+
+/// Serves the static files in the directory,
+/// usually the output of `flutter build web`,
+/// with headers required by the WebAssembly module.
+/// This function blocks the thread until the process is terminated.
+pub fn serve_web_directory(
+  serve_dir: &Path,
+  port: u16,
+) -> Result<(), SetupError> {
+  if !serve_dir.join("index.html").is_file() {
+    return Err(SetupError::BadFilePath(serve_dir.to_owned()));
+  }
+  let listener = TcpListener::bind(("127.0.0.1", port))?;
+  dimmedln!(
+    "Serving `{}` at http://localhost:{port}",
+    serve_dir.display()
   );
-  let full_command = format!("{base_command}{release_arg}");
-  clipboard.set_text(full_command)?;
+  dimmedln!("Press Ctrl+C to stop the server");
+
+  for stream_result in listener.incoming() {
+    let stream = match stream_result {
+      Ok(inner) => inner,
+      Err(err) => {
+        report_warning(&err.to_string());
+        continue;
+      }
+    };
+    let serve_dir = serve_dir.to_owned();
+    spawn(move || {
+      if let Err(err) = handle_connection(stream, &serve_dir) {
+        report_warning(&err.to_string());
+      }
+    });
+  }
+
+  Ok(())
+}
+
+fn handle_connection(
+  stream: TcpStream,
+  serve_dir: &Path,
+) -> Result<(), SetupError> {
+  // Read the request line and skip the remaining headers.
+  let mut reader = BufReader::new(&stream);
+  let mut request_line = String::new();
+  reader.read_line(&mut request_line)?;
+  loop {
+    let mut header_line = String::new();
+    let read_size = reader.read_line(&mut header_line)?;
+    if read_size == 0 || header_line.trim().is_empty() {
+      break;
+    }
+  }
+
+  // Only simple `GET` and `HEAD` requests are needed for static files.
+  let mut parts = request_line.split_whitespace();
+  let method = parts.next().unwrap_or_default();
+  let target = parts.next().unwrap_or_default();
+  let is_head = method == "HEAD";
+  if method != "GET" && !is_head {
+    return write_response(&stream, "405 Method Not Allowed", None, is_head);
+  }
+
+  match resolve_file_path(serve_dir, target) {
+    Some(file_path) => {
+      let body = read(&file_path)?;
+      let content_type = get_content_type(&file_path);
+      write_response(&stream, "200 OK", Some((content_type, body)), is_head)
+    }
+    None => write_response(&stream, "404 Not Found", None, is_head),
+  }
+}
+
+/// Maps the request target to a file inside the served directory.
+/// Paths that try to escape the directory are rejected.
+/// Unknown paths without an extension fall back to `index.html`
+/// so that Flutter's path URL strategy keeps working.
+fn resolve_file_path(serve_dir: &Path, target: &str) -> Option<PathBuf> {
+  let url_path = target.split(['?', '#']).next().unwrap_or_default();
+  let decoded = decode_percent(url_path)?;
+  let relative = Path::new(decoded.trim_start_matches('/'));
+  let is_safe = relative
+    .components()
+    .all(|component| matches!(component, Component::Normal(_)));
+  if !is_safe {
+    return None;
+  }
+
+  let mut file_path = serve_dir.join(relative);
+  if file_path.is_dir() {
+    file_path = file_path.join("index.html");
+  }
+  if file_path.is_file() {
+    Some(file_path)
+  } else if relative.extension().is_none() {
+    Some(serve_dir.join("index.html"))
+  } else {
+    None
+  }
+}
+
+fn decode_percent(text: &str) -> Option<String> {
+  let bytes = text.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+  while index < bytes.len() {
+    if bytes[index] == b'%' {
+      let hex = text.get(index + 1..index + 3)?;
+      decoded.push(u8::from_str_radix(hex, 16).ok()?);
+      index += 3;
+    } else {
+      decoded.push(bytes[index]);
+      index += 1;
+    }
+  }
+  String::from_utf8(decoded).ok()
+}
+
+fn get_content_type(file_path: &Path) -> &'static str {
+  let extension = file_path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .unwrap_or_default()
+    .to_lowercase();
+  match extension.as_str() {
+    "html" => "text/html; charset=utf-8",
+    "js" | "mjs" => "text/javascript; charset=utf-8",
+    "css" => "text/css; charset=utf-8",
+    "json" | "map" => "application/json",
+    "wasm" => "application/wasm",
+    "png" => "image/png",
+    "jpg" | "jpeg" => "image/jpeg",
+    "gif" => "image/gif",
+    "svg" => "image/svg+xml",
+    "ico" => "image/x-icon",
+    "webp" => "image/webp",
+    "ttf" => "font/ttf",
+    "otf" => "font/otf",
+    "woff" => "font/woff",
+    "woff2" => "font/woff2",
+    "txt" => "text/plain; charset=utf-8",
+    _ => "application/octet-stream",
+  }
+}
+
+fn write_response(
+  mut stream: &TcpStream,
+  status: &str,
+  content: Option<(&str, Vec<u8>)>,
+  is_head: bool,
+) -> Result<(), SetupError> {
+  let (content_type, body) =
+    content.unwrap_or(("text/plain; charset=utf-8", status.into()));
+  let mut head = format!("HTTP/1.1 {status}\r\n");
+  for (name, value) in CROSS_ORIGIN_HEADERS {
+    head.push_str(&format!("{name}: {value}\r\n"));
+  }
+  head.push_str(&format!("content-type: {content_type}\r\n"));
+  head.push_str(&format!("content-length: {}\r\n", body.len()));
+  head.push_str("cache-control: no-cache\r\n");
+  head.push_str("connection: close\r\n\r\n");
+  stream.write_all(head.as_bytes())?;
+  if !is_head {
+    stream.write_all(&body)?;
+  }
+  stream.flush()?;
   Ok(())
 }
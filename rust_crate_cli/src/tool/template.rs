@@ -47,7 +47,7 @@ pub fn apply_rust_template(
     .capture_err()?;
 
   // Generate Dart code.
-  generate_dart_code(root_dir, rinf_config)?;
+  generate_dart_code(root_dir, rinf_config, None)?;
 
   Ok(())
 }
@@ -151,6 +151,11 @@ to generate the corresponding Dart classes:
 rinf gen
 ```
 
+This also assigns endpoint IDs to the signals
+and writes them in `native/hub/rinf_endpoints.txt`,
+which should be committed along with the Rust code.
+Rust signals are identified by these IDs instead of their names.
+
 Now you can run and build this app just like any other Flutter projects.
 
 ```shell
@@ -1,10 +1,10 @@
-use crate::dimmedln;
-use crate::tool::{CaptureError, SetupError};
+use crate::tool::{CaptureError, RinfConfig, SetupError, report_progress};
 use std::path::Path;
 use std::process::Command;
 
 pub fn build_webassembly(
   root_dir: &Path,
+  rinf_config: &RinfConfig,
   is_release_mode: bool,
   is_internet_connected: bool,
 ) -> Result<(), SetupError> {
@@ -13,34 +13,26 @@ pub fn build_webassembly(
 
   step += 1;
   if is_internet_connected {
-    dimmedln!(
-      "[{}/{}] Installing Rust toolchain for the web",
-      step,
-      total_steps
-    );
+    report_progress(step, total_steps, "Installing Rust toolchain for the web");
     install_wasm_toolchain()?;
   } else {
-    dimmedln!(
-      "[{}/{}] Skipping ensurement of Rust toolchain for the web",
+    report_progress(
       step,
-      total_steps
+      total_steps,
+      "Skipping ensurement of Rust toolchain for the web",
     );
   }
 
   step += 1;
-  dimmedln!(
-    "[{}/{}] Preparing WebAssembly output path",
-    step,
-    total_steps
-  );
+  report_progress(step, total_steps, "Preparing WebAssembly output path");
 
   step += 1;
-  dimmedln!(
-    "[{}/{}] Compiling Rust with `wasm-pack` to `web` target",
+  report_progress(
     step,
-    total_steps
+    total_steps,
+    "Compiling Rust with `wasm-pack` to `web` target",
   );
-  compile_wasm(root_dir, is_release_mode)?;
+  compile_wasm(root_dir, rinf_config, is_release_mode)?;
 
   Ok(())
 }
@@ -75,8 +67,13 @@ fn install_wasm_toolchain() -> Result<(), SetupError> {
 
 fn compile_wasm(
   root_dir: &Path,
+  rinf_config: &RinfConfig,
   is_release_mode: bool,
 ) -> Result<(), SetupError> {
+  let hub_path = rinf_config.native_crate_dir(root_dir, "hub");
+  let hub_string = hub_path
+    .to_str()
+    .ok_or_else(|| SetupError::BadFilePath(hub_path.clone()))?;
   let out_path = root_dir.join("web").join("pkg");
   let out_string = out_path
     .to_str()
@@ -84,7 +81,7 @@ fn compile_wasm(
   let mut wasm_pack_args = vec![
     "--quiet",
     "build",
-    "native/hub",
+    hub_string,
     "--out-dir",
     out_string,
     "--out-name",
@@ -1,6 +1,9 @@
-use heck::{ToShoutySnakeCase, ToSnakeCase};
+use heck::ToShoutySnakeCase;
 use proc_macro::TokenStream;
 use quote::quote;
+use std::env::var;
+use std::fs::read_to_string;
+use std::path::PathBuf;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
 use syn::{
@@ -10,6 +13,10 @@ use syn::{
 
 static BANNED_LOWER_PREFIX: &str = "rinf";
 
+/// Name of the file in the crate root
+/// where `rinf gen` writes endpoint IDs of signals.
+static ENDPOINT_TABLE_FILE: &str = "rinf_endpoints.txt";
+
 /// Marks the struct as a signal
 /// that can be nested within other signals.
 /// A `SignalPiece` cannot be sent independently
@@ -71,7 +78,6 @@ fn derive_dart_signal_real(
   let ast = parse_macro_input!(input as DeriveInput);
   let name = &ast.ident;
   let name_lit = name.to_string();
-  let snake_name = name_lit.to_snake_case();
   let upper_snake_name = name_lit.to_shouty_snake_case();
 
   // Check the name.
@@ -96,14 +102,18 @@ fn derive_dart_signal_real(
     _ => return TokenStream::new(),
   };
 
+  // Get the endpoint ID assigned by `rinf gen`.
+  let endpoint_id_impl = match get_endpoint_id_impl(name) {
+    Ok(inner) => inner,
+    Err(error) => return error.to_compile_error().into(),
+  };
+
   // Collect identifiers and names.
   let channel_type_ident = Ident::new(&format!("{name}Channel"), name.span());
   let channel_const_ident =
     Ident::new(&format!("{upper_snake_name}_CHANNEL"), name.span());
-  let extern_fn_name = &format!("rinf_send_dart_signal_{snake_name}");
-  let extern_fn_ident = Ident::new(extern_fn_name, name.span());
 
-  // Implement methods and extern functions.
+  // Implement methods and register the endpoint.
   let signal_trait = if include_binary {
     quote! { rinf::DartSignalBinary }
   } else {
@@ -118,7 +128,7 @@ fn derive_dart_signal_real(
     }
 
     impl #name #where_clause {
-      fn send_dart_signal(message_bytes: &[u8], binary: &[u8]) {
+      fn send_dart_signal(message_bytes: &[u8], binary: Vec<u8>) {
         use rinf::{AppError, DartSignalPack, debug_print, deserialize};
         let message_result: Result<#name, AppError> =
           deserialize(message_bytes)
@@ -133,7 +143,7 @@ fn derive_dart_signal_real(
         };
         let dart_signal = DartSignalPack {
           message,
-          binary: binary.to_vec(),
+          binary,
         };
         #channel_const_ident.0.send(dart_signal);
       }
@@ -147,24 +157,17 @@ fn derive_dart_signal_real(
     static #channel_const_ident: #channel_type_ident =
       std::sync::LazyLock::new(rinf::signal_channel);
 
-    #[cfg(not(target_family = "wasm"))]
-    #[unsafe(no_mangle)]
-    unsafe extern "C" fn #extern_fn_ident(
-      message_pointer: *const u8,
-      message_size: usize,
-      binary_pointer: *const u8,
-      binary_size: usize,
-    ) {
-      use std::slice::from_raw_parts;
-      let message_bytes = from_raw_parts(message_pointer, message_size);
-      let binary = from_raw_parts(binary_pointer, binary_size);
-      #name::send_dart_signal(message_bytes, binary);
+    impl rinf::DartSignalId for #name {
+      #endpoint_id_impl
     }
 
-    #[cfg(target_family = "wasm")]
-    #[wasm_bindgen::prelude::wasm_bindgen]
-    pub fn #extern_fn_ident(message_bytes: &[u8], binary: &[u8]) {
-      #name::send_dart_signal(message_bytes, binary);
+    // Register the endpoint for dispatching from Dart.
+    rinf::inventory::submit! {
+      rinf::DartSignalEndpoint {
+        id: <#name as rinf::DartSignalId>::ENDPOINT_ID,
+        name: #name_lit,
+        handler: #name::send_dart_signal,
+      }
     }
   };
 
@@ -219,13 +222,30 @@ fn derive_rust_signal_real(
     _ => return TokenStream::new(),
   };
 
-  // Implement methods and extern functions.
+  // Get the endpoint ID assigned by `rinf gen`.
+  let endpoint_id_impl = match get_endpoint_id_impl(name) {
+    Ok(inner) => inner,
+    Err(error) => return error.to_compile_error().into(),
+  };
+
+  // Implement methods and provide the endpoint ID.
+  let endpoint_id_impl = quote! {
+    impl rinf::RustSignalId for #name {
+      #endpoint_id_impl
+    }
+  };
   let expanded = if include_binary {
     quote! {
+      #endpoint_id_impl
+
       impl rinf::RustSignalBinary for #name #where_clause {
         fn send_signal_to_dart(&self, binary: Vec<u8>) {
-          use rinf::{AppError, debug_print, send_rust_signal, serialize};
+          use rinf::{
+            AppError, RustSignalId, debug_print, send_rust_signal, serialize,
+          };
+          // The type name is only used for debugging.
           let type_name = #name_lit;
+          const ENDPOINT_ID: u32 = <#name as RustSignalId>::ENDPOINT_ID;
           let message_result: Result<Vec<u8>, AppError> =
             serialize(&self)
             .map_err(|_| AppError::CannotEncodeMessage);
@@ -236,7 +256,7 @@ fn derive_rust_signal_real(
               return;
             }
           };
-          let result = send_rust_signal(type_name, message_bytes, binary);
+          let result = send_rust_signal(ENDPOINT_ID, message_bytes, binary);
           if let Err(err) = result {
             debug_print!("{}: \n{}", type_name, err);
           }
@@ -245,10 +265,16 @@ fn derive_rust_signal_real(
     }
   } else {
     quote! {
+      #endpoint_id_impl
+
       impl rinf::RustSignal for #name #where_clause {
         fn send_signal_to_dart(&self) {
-          use rinf::{AppError, debug_print, send_rust_signal, serialize};
+          use rinf::{
+            AppError, RustSignalId, debug_print, send_rust_signal, serialize,
+          };
+          // The type name is only used for debugging.
           let type_name = #name_lit;
+          const ENDPOINT_ID: u32 = <#name as RustSignalId>::ENDPOINT_ID;
           let message_result: Result<Vec<u8>, AppError> =
             serialize(&self)
             .map_err(|_| AppError::CannotEncodeMessage);
@@ -259,7 +285,7 @@ fn derive_rust_signal_real(
               return;
             }
           };
-          let result = send_rust_signal(type_name, message_bytes, Vec::new());
+          let result = send_rust_signal(ENDPOINT_ID, message_bytes, Vec::new());
           if let Err(err) = result {
             debug_print!("{}: \n{}", type_name, err);
           }
@@ -551,6 +577,39 @@ impl GetAttrs for (usize, &Field) {
   }
 }
 
+/// Reads the endpoint ID of the signal
+/// from the table file that `rinf gen` wrote in the crate root.
+/// The table file is included in the generated code
+/// so that Cargo rebuilds the crate when the IDs change.
+fn get_endpoint_id_impl(name: &Ident) -> Result<proc_macro2::TokenStream> {
+  let manifest_dir = var("CARGO_MANIFEST_DIR").unwrap_or_default();
+  let table_path = PathBuf::from(manifest_dir).join(ENDPOINT_TABLE_FILE);
+  let name_lit = name.to_string();
+  let endpoint_id = read_to_string(&table_path)
+    .unwrap_or_default()
+    .lines()
+    .filter(|line| !line.starts_with('#'))
+    .filter_map(|line| line.split_once('='))
+    .find(|(entry_name, _)| entry_name.trim() == name_lit)
+    .and_then(|(_, id)| id.trim().parse::<u32>().ok());
+  let Some(endpoint_id) = endpoint_id else {
+    return Err(Error::new_spanned(
+      name,
+      format!(
+        "No endpoint ID is assigned to `{name_lit}`. \
+        Run `rinf gen` to write it in `{ENDPOINT_TABLE_FILE}`"
+      ),
+    ));
+  };
+  let table_path_lit = table_path.to_string_lossy().into_owned();
+  Ok(quote! {
+    const ENDPOINT_ID: u32 = {
+      const _: &str = include_str!(#table_path_lit);
+      #endpoint_id
+    };
+  })
+}
+
 fn create_generic_error(ast: DeriveInput) -> TokenStream {
   Error::new_spanned(ast.generics, "A foreign signal type cannot be generic")
     .to_compile_error()